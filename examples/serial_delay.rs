@@ -43,8 +43,10 @@ fn main() -> ! {
             baud_rate: 115_200.bps(),
             oversampling: serial::Oversampling::By16,
             character_match: None,
+            ..Default::default()
         },
-    );
+    )
+    .unwrap();
     let (mut tx, _) = serial.split();
 
     let hello: &str = "Hello, I'm a STM32F7xx!\r\n";
@@ -50,8 +50,10 @@ fn main() -> ! {
             baud_rate: 115_200.bps(),
             oversampling: serial::Oversampling::By16,
             character_match: None,
+            ..Default::default()
         },
-    );
+    )
+    .unwrap();
     let (mut tx, mut rx) = serial.split();
 
     // Create the buffer we're going to use for DMA. This is safe, as this
@@ -39,8 +39,10 @@ fn main() -> ! {
             baud_rate: 115_200.bps(),
             oversampling: serial::Oversampling::By16,
             character_match: None,
+            ..Default::default()
         },
-    );
+    )
+    .unwrap();
 
     let (mut tx, mut rx) = serial.split();
 
@@ -0,0 +1,136 @@
+#![no_main]
+#![no_std]
+
+extern crate panic_semihosting;
+
+use core::{cell::RefCell, pin::Pin};
+
+use cortex_m::{
+    asm,
+    interrupt::{free, Mutex},
+    peripheral::NVIC,
+};
+use cortex_m_rt::entry;
+use stm32f7xx_hal::{
+    gpio::{gpioc::PC10, gpioc::PC11, gpioc::PC12, Alternate, AF6},
+    interrupt, pac,
+    prelude::*,
+    spi::{self, InterruptTransfer, Spi},
+};
+
+type Transfer = InterruptTransfer<
+    u8,
+    pac::SPI3,
+    (
+        PC10<Alternate<AF6>>,
+        PC11<Alternate<AF6>>,
+        PC12<Alternate<AF6>>,
+    ),
+    &'static mut [u8; 2],
+>;
+
+static TRANSFER: Mutex<RefCell<Option<Transfer>>> = Mutex::new(RefCell::new(None));
+
+#[entry]
+fn main() -> ! {
+    let p = pac::Peripherals::take().unwrap();
+
+    let mut rcc = p.RCC.constrain();
+
+    let gpiob = p.GPIOB.split();
+    let gpioc = p.GPIOC.split();
+
+    // Prepare status LEDS. These happen to be the red and green ones on the
+    // NUCLEO-F746ZG board.
+    let mut green = gpiob.pb0.into_push_pull_output();
+    let mut red = gpiob.pb14.into_push_pull_output();
+
+    // Prepare pins for SPI
+    let mut ncs = gpioc.pc9.into_push_pull_output();
+    let sck = gpioc.pc10.into_alternate_af6();
+    let miso = gpioc.pc11.into_alternate_af6();
+    let mosi = gpioc.pc12.into_alternate_af6();
+
+    // Set NCS pin to high (disabled) initially
+    ncs.set_high().unwrap();
+
+    // Initialize SPI
+    let mut spi = Spi::new(p.SPI3, (sck, miso, mosi)).enable(
+        &mut rcc,
+        spi::ClockDivider::DIV32,
+        spi::Mode {
+            polarity: spi::Polarity::IdleHigh,
+            phase: spi::Phase::CaptureOnSecondTransition,
+        },
+    );
+
+    // Create the buffer we're going to use for the transfer. This is safe, as
+    // this function won't return as long as the program runs, so there's no
+    // chance of anyone else using the same static.
+    static mut BUFFER: [u8; 2] = [0; 2];
+    let mut buffer = unsafe { Pin::new(&mut BUFFER) };
+
+    unsafe {
+        NVIC::unmask(pac::Interrupt::SPI3);
+    }
+
+    loop {
+        // Read WHO_AM_I register of an MPU9250 sensor.
+        // Write address for WHO_AM_I register an an MPU9250 sensor
+        buffer[0] = 0x75 | 0x80;
+
+        // Start the transfer. From here on, the interrupt handler moves the
+        // data.
+        ncs.set_low().unwrap();
+        free(|cs| {
+            let transfer = spi.transfer_interrupt(buffer);
+            TRANSFER.borrow(cs).replace(Some(transfer));
+        });
+
+        // Wait for the transfer to finish. The check and `wfi` happen in the
+        // same critical section, so the interrupt that finishes the transfer
+        // can't slip in between them. A pending interrupt still wakes up
+        // `wfi`, and is handled once the critical section ends.
+        let res = loop {
+            let transfer = free(|cs| {
+                let mut transfer = TRANSFER.borrow(cs).borrow_mut();
+                match transfer.as_ref().map(|transfer| transfer.is_active()) {
+                    Some(false) => transfer.take(),
+                    _ => {
+                        asm::wfi();
+                        None
+                    }
+                }
+            });
+
+            if let Some(transfer) = transfer {
+                break transfer.wait().unwrap();
+            }
+        };
+        ncs.set_high().unwrap();
+
+        // Assign everything we've moved to the transfer to the local variables
+        // it came from, so it's available again in the next loop iteration.
+        buffer = res.buffer;
+        spi = res.target;
+
+        // The WHO_AM_I register should always return 0x71.
+        if buffer[1] == 0x71 {
+            green.set_high().unwrap();
+            red.set_low().unwrap();
+        } else {
+            red.set_high().unwrap();
+            green.set_low().unwrap();
+        }
+    }
+}
+
+#[interrupt]
+fn SPI3() {
+    free(|cs| {
+        if let Some(transfer) = TRANSFER.borrow(cs).borrow_mut().as_mut() {
+            // Errors are reported again by `wait`
+            let _ = transfer.on_interrupt();
+        }
+    });
+}
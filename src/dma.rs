@@ -79,14 +79,16 @@ where
 ///
 /// Peripheral APIs that support DMA have methods like `write_all` and
 /// `read_all`, which return instances of this struct.
-pub struct Transfer<T: Target, B, State> {
-    res: TransferResources<T, B>,
+pub struct Transfer<T: Target<S>, S: Stream, B, State> {
+    res: TransferResources<T, S, B>,
+    len: usize,
     _state: State,
 }
 
-impl<T, B> Transfer<T, B, Ready>
+impl<T, S, B> Transfer<T, S, B, Ready>
 where
-    T: Target,
+    T: Target<S>,
+    S: Stream,
     B: 'static,
 {
     /// Internal constructor to create a new `Transfer`
@@ -102,8 +104,8 @@ where
     /// If this method is used to prepare a peripheral-to-memory transfer, the
     /// caller must make sure that the buffer can be written to safely.
     pub(crate) unsafe fn new<Word>(
-        handle: &Handle<T::Instance, state::Enabled>,
-        stream: T::Stream,
+        handle: &Handle<S::Instance, state::Enabled>,
+        stream: S,
         buffer: Pin<B>,
         target: T,
         address: u32,
@@ -115,17 +117,18 @@ where
         Word: SupportedWordSize,
     {
         assert!(buffer.len() <= u16::max_value() as usize);
+        let len = buffer.len();
 
         // The following configuration procedure is documented in the reference
         // manual for STM32F75xxx and STM32F74xxx, section 8.3.18.
 
-        let nr = T::Stream::number();
+        let nr = S::number();
 
         // Disable stream
         handle.dma.st[nr].cr.modify(|_, w| w.en().disabled());
         while handle.dma.st[nr].cr.read().en().is_enabled() {}
 
-        T::Stream::clear_status_flags(&handle.dma);
+        S::clear_status_flags(&handle.dma);
 
         // Set peripheral port register address
         handle.dma.st[nr].par.write(|w| w.pa().bits(address));
@@ -211,6 +214,7 @@ where
                 buffer,
                 target,
             },
+            len,
             _state: Ready,
         }
     }
@@ -222,10 +226,10 @@ where
     /// DMA stream.
     pub fn enable_interrupts(
         &mut self,
-        handle: &Handle<T::Instance, state::Enabled>,
+        handle: &Handle<S::Instance, state::Enabled>,
         interrupts: Interrupts,
     ) {
-        handle.dma.st[T::Stream::number()].cr.modify(|_, w| {
+        handle.dma.st[S::number()].cr.modify(|_, w| {
             let w = if interrupts.transfer_complete {
                 w.tcie().enabled()
             } else {
@@ -254,38 +258,46 @@ where
         });
 
         // Enable interrupt.
-        unsafe { NVIC::unmask(T::INTERRUPT) };
+        unsafe { NVIC::unmask(S::INTERRUPT) };
+    }
+
+    /// Enables circular mode
+    ///
+    /// The transfer restarts from the beginning of the buffer once it reaches
+    /// the end, until it is stopped.
+    pub(crate) fn enable_circular(&mut self, handle: &Handle<S::Instance, state::Enabled>) {
+        handle.dma.st[S::number()]
+            .cr
+            .modify(|_, w| w.circ().enabled());
     }
 
     /// Start the DMA transfer
     ///
     /// Consumes this instance of `Transfer` and returns another instance with
     /// its type state set to indicate the transfer has been started.
-    pub fn start(self, handle: &Handle<T::Instance, state::Enabled>) -> Transfer<T, B, Started> {
+    pub fn start(self, handle: &Handle<S::Instance, state::Enabled>) -> Transfer<T, S, B, Started> {
         atomic::fence(Ordering::SeqCst);
 
-        handle.dma.st[T::Stream::number()]
+        handle.dma.st[S::number()]
             .cr
             .modify(|_, w| w.en().enabled());
 
         Transfer {
             res: self.res,
+            len: self.len,
             _state: Started,
         }
     }
 }
 
-impl<T, B> Transfer<T, B, Started>
+impl<T, S, B> Transfer<T, S, B, Started>
 where
-    T: Target,
+    T: Target<S>,
+    S: Stream,
 {
     /// Checks whether the transfer is still ongoing
-    pub fn is_active(&self, handle: &Handle<T::Instance, state::Enabled>) -> bool {
-        handle.dma.st[T::Stream::number()]
-            .cr
-            .read()
-            .en()
-            .is_enabled()
+    pub fn is_active(&self, handle: &Handle<S::Instance, state::Enabled>) -> bool {
+        handle.dma.st[S::number()].cr.read().en().is_enabled()
     }
 
     /// Waits for the transfer to end
@@ -298,23 +310,20 @@ where
     /// data buffer, the DMA stream, and the peripheral. Those have been moved
     /// into the `Transfer` instance to prevent concurrent access to them. This
     /// method returns those resources, so they can be used again.
-    pub fn wait(
-        self,
-        handle: &Handle<T::Instance, state::Enabled>,
-    ) -> Result<TransferResources<T, B>, (TransferResources<T, B>, Error)> {
+    pub fn wait(self, handle: &Handle<S::Instance, state::Enabled>) -> TransferResult<T, S, B> {
         // Disable interrupt.
-        NVIC::mask(T::INTERRUPT);
+        NVIC::mask(S::INTERRUPT);
 
         // Wait for transfer to finish
         while self.is_active(handle) {
-            if let Err(error) = Error::check::<T::Stream>(&handle.dma) {
+            if let Err(error) = Error::check::<S>(&handle.dma) {
                 return Err((self.res, error));
             }
         }
 
         atomic::fence(Ordering::SeqCst);
 
-        if let Err(error) = Error::check::<T::Stream>(&handle.dma) {
+        if let Err(error) = Error::check::<S>(&handle.dma) {
             return Err((self.res, error));
         }
 
@@ -322,9 +331,128 @@ where
     }
 }
 
+impl<T, S, B> Transfer<T, S, B, Started>
+where
+    T: Target<S>,
+    S: Stream,
+{
+    /// Returns the buffer of the transfer
+    pub(crate) fn buffer(&self) -> &Pin<B> {
+        &self.res.buffer
+    }
+
+    /// Returns the number of data items that are left to transfer (NDTR)
+    pub(crate) fn remaining(&self, handle: &Handle<S::Instance, state::Enabled>) -> usize {
+        handle.dma.st[S::number()].ndtr.read().ndt().bits() as usize
+    }
+
+    /// Returns the number of data items the transfer was started with
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the transfer complete flag is set, and clears it
+    pub(crate) fn take_transfer_complete(
+        &self,
+        handle: &Handle<S::Instance, state::Enabled>,
+    ) -> bool {
+        if S::is_transfer_complete(&handle.dma) {
+            S::clear_transfer_complete(&handle.dma);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns whether the half transfer flag is set, and clears it
+    pub(crate) fn take_half_transfer(&self, handle: &Handle<S::Instance, state::Enabled>) -> bool {
+        if S::is_half_transfer(&handle.dma) {
+            S::clear_half_transfer(&handle.dma);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns an error, if the transfer has failed
+    pub(crate) fn check_error(
+        &self,
+        handle: &Handle<S::Instance, state::Enabled>,
+    ) -> Result<(), Error> {
+        Error::check::<S>(&handle.dma)
+    }
+
+    /// Stops the transfer and rewinds it to the beginning of the buffer
+    ///
+    /// The transfer can be continued using [`resume`](Transfer::resume).
+    /// Returns the number of data items that had been transferred.
+    pub(crate) fn rewind(&mut self, handle: &Handle<S::Instance, state::Enabled>) -> usize {
+        let nr = S::number();
+
+        handle.dma.st[nr].cr.modify(|_, w| w.en().disabled());
+        while handle.dma.st[nr].cr.read().en().is_enabled() {}
+
+        atomic::fence(Ordering::SeqCst);
+
+        let remaining = handle.dma.st[nr].ndtr.read().ndt().bits() as usize;
+        S::clear_status_flags(&handle.dma);
+
+        // We've asserted that `len` fits into a `u16` when the transfer was
+        // created.
+        handle.dma.st[nr]
+            .ndtr
+            .write(|w| w.ndt().bits(self.len as u16));
+
+        self.len - remaining
+    }
+
+    /// Enables the stream again after [`rewind`](Transfer::rewind)
+    pub(crate) fn resume(&mut self, handle: &Handle<S::Instance, state::Enabled>) {
+        atomic::fence(Ordering::SeqCst);
+
+        handle.dma.st[S::number()]
+            .cr
+            .modify(|_, w| w.en().enabled());
+    }
+
+    /// Stops the transfer, whether it has finished or not
+    ///
+    /// This is useful for peripheral-to-memory transfers whose length is not
+    /// known in advance, e.g. a serial reception that ends on an idle line.
+    /// Returns the resources, together with the number of data items that
+    /// have been transferred.
+    pub fn stop(
+        self,
+        handle: &Handle<S::Instance, state::Enabled>,
+    ) -> (TransferResources<T, S, B>, usize) {
+        // Disable interrupt.
+        NVIC::mask(S::INTERRUPT);
+
+        let nr = S::number();
+
+        // Disable stream. Data items that are in flight are still
+        // transferred before EN reads as cleared.
+        handle.dma.st[nr].cr.modify(|_, w| w.en().disabled());
+        while handle.dma.st[nr].cr.read().en().is_enabled() {}
+
+        atomic::fence(Ordering::SeqCst);
+
+        let remaining = handle.dma.st[nr].ndtr.read().ndt().bits() as usize;
+        S::clear_status_flags(&handle.dma);
+
+        (self.res, self.len - remaining)
+    }
+}
+
+/// The result of [`Transfer::wait`]
+///
+/// On error, the resources are returned along with the error.
+pub type TransferResult<T, S, B> =
+    Result<TransferResources<T, S, B>, (TransferResources<T, S, B>, Error)>;
+
 /// The resources that an ongoing transfer needs exclusive access to
-pub struct TransferResources<T: Target, B> {
-    pub stream: T::Stream,
+pub struct TransferResources<T: Target<S>, S: Stream, B> {
+    pub stream: S,
     pub buffer: Pin<B>,
     pub target: T,
 }
@@ -332,9 +460,10 @@ pub struct TransferResources<T: Target, B> {
 // As `TransferResources` is used in the error variant of `Result`, it needs a
 // `Debug` implementation to enable stuff like `unwrap` and `expect`. This can't
 // be derived without putting requirements on the type arguments.
-impl<T, B> fmt::Debug for TransferResources<T, B>
+impl<T, S, B> fmt::Debug for TransferResources<T, S, B>
 where
-    T: Target,
+    T: Target<S>,
+    S: Stream,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "TransferResources {{ .. }}")
@@ -348,14 +477,14 @@ pub(crate) enum Direction {
 
 /// Implemented for all peripheral APIs that support DMA transfers
 ///
+/// A peripheral implements this trait once for every stream it can be used
+/// with. The stream that is passed when setting up a transfer selects the
+/// combination, and with it the channel.
+///
 /// This is an internal trait. End users neither need to implement it, nor use
 /// it directly.
-pub trait Target {
-    type Instance: Deref<Target = dma2::RegisterBlock>;
-    type Stream: Stream;
+pub trait Target<S: Stream> {
     type Channel: Channel;
-
-    const INTERRUPT: Interrupt;
 }
 
 macro_rules! impl_target {
@@ -364,77 +493,64 @@ macro_rules! impl_target {
             $ty:ty,
             $instance:ty,
             $stream:ident,
-            $channel:ty,
-            $interrupt:ident;
+            $channel:ty;
         )*
     ) => {
         $(
-            impl Target for $ty {
-                type Instance = $instance;
-                type Stream   = $stream<$instance>;
-                type Channel  = $channel;
-
-                const INTERRUPT: Interrupt = Interrupt::$interrupt;
+            impl Target<$stream<$instance>> for $ty {
+                type Channel = $channel;
             }
         )*
     }
 }
 
 // See section 8.3.4, tables 25 and 26
-//
-// Some peripherals can be used with multiple stream/channel combinations. To
-// model this, we'd need to implement `Tx` multiple times, which means `Tx`
-// would need a type parameter. When attempting to do this, I ran into errors
-// about unconstrained type parameters when trying to use `Tx`.
-//
-// There's probably a smart way to achieve this, but I decided to declare
-// victory and leave this problem to someone who actually needs this capability.
 impl_target!(
     // SPI receive
-    spi::Rx<pac::SPI1>, DMA2, Stream0, Channel3, DMA2_STREAM0;
-    // SPI1 for DMA2, stream 2, channel 3 is unsupported
-    spi::Rx<pac::SPI2>, DMA1, Stream3, Channel0, DMA1_STREAM3;
-    spi::Rx<pac::SPI3>, DMA1, Stream0, Channel0, DMA1_STREAM0;
-    // SPI3 for DMA1, stream 2, channel 0 is unsupported
-    spi::Rx<pac::SPI4>, DMA2, Stream0, Channel4, DMA2_STREAM0;
-    // SPI4 for DMA2, stream 3, channel 5 is unsupported
-    spi::Rx<pac::SPI5>, DMA2, Stream3, Channel2, DMA2_STREAM3;
-    // SPI5 for DMA2, stream 5, channel 7 is unsupported
+    spi::Rx<pac::SPI1>, DMA2, Stream0, Channel3;
+    spi::Rx<pac::SPI1>, DMA2, Stream2, Channel3;
+    spi::Rx<pac::SPI2>, DMA1, Stream3, Channel0;
+    spi::Rx<pac::SPI3>, DMA1, Stream0, Channel0;
+    spi::Rx<pac::SPI3>, DMA1, Stream2, Channel0;
+    spi::Rx<pac::SPI4>, DMA2, Stream0, Channel4;
+    spi::Rx<pac::SPI4>, DMA2, Stream3, Channel5;
+    spi::Rx<pac::SPI5>, DMA2, Stream3, Channel2;
+    spi::Rx<pac::SPI5>, DMA2, Stream5, Channel7;
 
     // SPI transmit
-    spi::Tx<pac::SPI1>, DMA2, Stream3, Channel3, DMA2_STREAM3;
-    // SPI1 for DMA2, stream 5, channel 3 is unsupported
-    spi::Tx<pac::SPI2>, DMA1, Stream4, Channel0, DMA1_STREAM4;
-    spi::Tx<pac::SPI3>, DMA1, Stream5, Channel0, DMA1_STREAM5;
-    // SPI3 for DMA1, stream 7, channel 0 is unsupported
-    spi::Tx<pac::SPI4>, DMA2, Stream1, Channel4, DMA2_STREAM1;
-    // SPI4 for DMA2, stream 4, channel 5 is unsupported
-    spi::Tx<pac::SPI5>, DMA2, Stream4, Channel2, DMA2_STREAM4;
-    // SPI5 for DMA2, stream 6, channel 7 is unsupported
+    spi::Tx<pac::SPI1>, DMA2, Stream3, Channel3;
+    spi::Tx<pac::SPI1>, DMA2, Stream5, Channel3;
+    spi::Tx<pac::SPI2>, DMA1, Stream4, Channel0;
+    spi::Tx<pac::SPI3>, DMA1, Stream5, Channel0;
+    spi::Tx<pac::SPI3>, DMA1, Stream7, Channel0;
+    spi::Tx<pac::SPI4>, DMA2, Stream1, Channel4;
+    spi::Tx<pac::SPI4>, DMA2, Stream4, Channel5;
+    spi::Tx<pac::SPI5>, DMA2, Stream4, Channel2;
+    spi::Tx<pac::SPI5>, DMA2, Stream6, Channel7;
 
     // USART receive
-    serial::Rx<pac::USART1>, DMA2, Stream2, Channel4, DMA2_STREAM2;
-    // USART1 for DMA2, stream 5, channel 4 is unsupported
-    serial::Rx<pac::USART2>, DMA1, Stream5, Channel4, DMA1_STREAM5;
-    serial::Rx<pac::USART3>, DMA1, Stream1, Channel4, DMA1_STREAM1;
-    serial::Rx<pac::UART4>, DMA1, Stream2, Channel4, DMA1_STREAM2;
-    serial::Rx<pac::UART5>, DMA1, Stream0, Channel4, DMA1_STREAM0;
-    serial::Rx<pac::USART6>, DMA2, Stream1, Channel5, DMA2_STREAM1;
-    // USART6 for DMA2, stream 2, channel 5 is unsupported
-    serial::Rx<pac::UART7>, DMA1, Stream3, Channel5, DMA1_STREAM3;
-    serial::Rx<pac::UART8>, DMA1, Stream6, Channel5, DMA1_STREAM6;
+    serial::Rx<pac::USART1>, DMA2, Stream2, Channel4;
+    serial::Rx<pac::USART1>, DMA2, Stream5, Channel4;
+    serial::Rx<pac::USART2>, DMA1, Stream5, Channel4;
+    serial::Rx<pac::USART3>, DMA1, Stream1, Channel4;
+    serial::Rx<pac::UART4>,  DMA1, Stream2, Channel4;
+    serial::Rx<pac::UART5>,  DMA1, Stream0, Channel4;
+    serial::Rx<pac::USART6>, DMA2, Stream1, Channel5;
+    serial::Rx<pac::USART6>, DMA2, Stream2, Channel5;
+    serial::Rx<pac::UART7>,  DMA1, Stream3, Channel5;
+    serial::Rx<pac::UART8>,  DMA1, Stream6, Channel5;
 
     // USART transmit
-    serial::Tx<pac::USART1>, DMA2, Stream7, Channel4, DMA2_STREAM7;
-    serial::Tx<pac::USART2>, DMA1, Stream6, Channel4, DMA1_STREAM6;
-    serial::Tx<pac::USART3>, DMA1, Stream3, Channel4, DMA1_STREAM3;
-    // USART3 for DMA1, stream 4, channel 7 is unsupported
-    serial::Tx<pac::UART4>,  DMA1, Stream4, Channel4, DMA1_STREAM4;
-    serial::Tx<pac::UART5>,  DMA1, Stream7, Channel4, DMA1_STREAM7;
-    serial::Tx<pac::USART6>, DMA2, Stream6, Channel5, DMA2_STREAM6;
-    // USART6 for DMA2, stream 7, channel 5 is unsupported
-    serial::Tx<pac::UART7>,  DMA1, Stream1, Channel5, DMA1_STREAM1;
-    serial::Tx<pac::UART8>,  DMA1, Stream0, Channel5, DMA1_STREAM0;
+    serial::Tx<pac::USART1>, DMA2, Stream7, Channel4;
+    serial::Tx<pac::USART2>, DMA1, Stream6, Channel4;
+    serial::Tx<pac::USART3>, DMA1, Stream3, Channel4;
+    serial::Tx<pac::USART3>, DMA1, Stream4, Channel7;
+    serial::Tx<pac::UART4>,  DMA1, Stream4, Channel4;
+    serial::Tx<pac::UART5>,  DMA1, Stream7, Channel4;
+    serial::Tx<pac::USART6>, DMA2, Stream6, Channel5;
+    serial::Tx<pac::USART6>, DMA2, Stream7, Channel5;
+    serial::Tx<pac::UART7>,  DMA1, Stream1, Channel5;
+    serial::Tx<pac::UART8>,  DMA1, Stream0, Channel5;
 );
 
 #[cfg(any(
@@ -449,8 +565,8 @@ impl_target!(
     feature = "stm32f779",
 ))]
 impl_target!(
-    spi::Rx<pac::SPI6>, DMA2, Stream6, Channel1, DMA2_STREAM6;
-    spi::Tx<pac::SPI6>, DMA2, Stream5, Channel1, DMA2_STREAM5;
+    spi::Rx<pac::SPI6>, DMA2, Stream6, Channel1;
+    spi::Tx<pac::SPI6>, DMA2, Stream5, Channel1;
 );
 
 /// Implemented for all types that represent DMA streams
@@ -458,9 +574,15 @@ impl_target!(
 /// This is an internal trait. End users neither need to implement it, nor use
 /// it directly.
 pub trait Stream {
+    type Instance: Instance;
+
+    const INTERRUPT: Interrupt;
+
     fn number() -> usize;
 
     fn clear_status_flags(dma: &dma2::RegisterBlock);
+    fn clear_transfer_complete(dma: &dma2::RegisterBlock);
+    fn clear_half_transfer(dma: &dma2::RegisterBlock);
 
     fn is_transfer_complete(dma: &dma2::RegisterBlock) -> bool;
     fn is_half_transfer(dma: &dma2::RegisterBlock) -> bool;
@@ -482,6 +604,8 @@ macro_rules! impl_stream {
             $htif:ident,
             $tcif:ident,
             $flag_clear_reg:ident,
+            $ctcif:ident,
+            $chtif:ident,
             ($($flag_clear_field:ident,)*);
         )*
     ) => {
@@ -501,7 +625,13 @@ macro_rules! impl_stream {
         $(
             pub struct $name<I>(PhantomData<I>);
 
-            impl<I> Stream for $name<I> {
+            impl<I> Stream for $name<I>
+                where I: Instance
+            {
+                type Instance = I;
+
+                const INTERRUPT: Interrupt = I::INTERRUPTS[$number];
+
                 fn number() -> usize { $number }
 
                 fn clear_status_flags(dma: &dma2::RegisterBlock) {
@@ -511,6 +641,14 @@ macro_rules! impl_stream {
                     );
                 }
 
+                fn clear_transfer_complete(dma: &dma2::RegisterBlock) {
+                    dma.$flag_clear_reg.write(|w| w.$ctcif().clear());
+                }
+
+                fn clear_half_transfer(dma: &dma2::RegisterBlock) {
+                    dma.$flag_clear_reg.write(|w| w.$chtif().clear());
+                }
+
                 fn is_transfer_complete(dma: &dma2::RegisterBlock) -> bool {
                     dma.$flag_reg.read().$tcif().is_complete()
                 }
@@ -534,28 +672,28 @@ macro_rules! impl_stream {
 impl_stream!(
     Stream0, stream0, 0,
         lisr, feif0, dmeif0, teif0, htif0, tcif0,
-        lifcr, (cfeif0, cdmeif0, cteif0, chtif0, ctcif0,);
+        lifcr, ctcif0, chtif0, (cfeif0, cdmeif0, cteif0, chtif0, ctcif0,);
     Stream1, stream1, 1,
         lisr, feif1, dmeif1, teif1, htif1, tcif1,
-        lifcr, (cfeif1, cdmeif1, cteif1, chtif1, ctcif1,);
+        lifcr, ctcif1, chtif1, (cfeif1, cdmeif1, cteif1, chtif1, ctcif1,);
     Stream2, stream2, 2,
         lisr, feif2, dmeif2, teif2, htif2, tcif2,
-        lifcr, (cfeif2, cdmeif2, cteif2, chtif2, ctcif2,);
+        lifcr, ctcif2, chtif2, (cfeif2, cdmeif2, cteif2, chtif2, ctcif2,);
     Stream3, stream3, 3,
         lisr, feif3, dmeif3, teif3, htif3, tcif3,
-        lifcr, (cfeif3, cdmeif3, cteif3, chtif3, ctcif3,);
+        lifcr, ctcif3, chtif3, (cfeif3, cdmeif3, cteif3, chtif3, ctcif3,);
     Stream4, stream4, 4,
         hisr, feif4, dmeif4, teif4, htif4, tcif4,
-        hifcr, (cfeif4, cdmeif4, cteif4, chtif4, ctcif4,);
+        hifcr, ctcif4, chtif4, (cfeif4, cdmeif4, cteif4, chtif4, ctcif4,);
     Stream5, stream5, 5,
         hisr, feif5, dmeif5, teif5, htif5, tcif5,
-        hifcr, (cfeif5, cdmeif5, cteif5, chtif5, ctcif5,);
+        hifcr, ctcif5, chtif5, (cfeif5, cdmeif5, cteif5, chtif5, ctcif5,);
     Stream6, stream6, 6,
         hisr, feif6, dmeif6, teif6, htif6, tcif6,
-        hifcr, (cfeif6, cdmeif6, cteif6, chtif6, ctcif6,);
+        hifcr, ctcif6, chtif6, (cfeif6, cdmeif6, cteif6, chtif6, ctcif6,);
     Stream7, stream7, 7,
         hisr, feif7, dmeif7, teif7, htif7, tcif7,
-        hifcr, (cfeif7, cdmeif7, cteif7, chtif7, ctcif7,);
+        hifcr, ctcif7, chtif7, (cfeif7, cdmeif7, cteif7, chtif7, ctcif7,);
 );
 
 /// Implemented for all types that represent DMA channels
@@ -599,14 +737,19 @@ impl_channel!(
 ///
 /// This is an internal trait. End users neither need to implement it, nor use
 /// it directly.
-pub trait Instance {
+pub trait Instance: Deref<Target = dma2::RegisterBlock> {
+    /// The interrupts of the streams, in order
+    const INTERRUPTS: [Interrupt; 8];
+
     fn enable(rcc: &mut Rcc);
 }
 
 macro_rules! impl_instance {
-    ($($name:ty, $reset_reg:ident, $enable_reg:ident;)*) => {
+    ($($name:ty, $reset_reg:ident, $enable_reg:ident, [$($interrupt:ident,)*];)*) => {
         $(
             impl Instance for $name {
+                const INTERRUPTS: [Interrupt; 8] = [$(Interrupt::$interrupt,)*];
+
                 fn enable(rcc: &mut Rcc) {
                     rcc.ahb1.rstr().modify(|_, w| w.$reset_reg().clear_bit());
                     rcc.ahb1.enr().modify(|_, w| w.$enable_reg().enabled());
@@ -617,8 +760,14 @@ macro_rules! impl_instance {
 }
 
 impl_instance!(
-    DMA1, dma1rst, dma1en;
-    DMA2, dma2rst, dma2en;
+    DMA1, dma1rst, dma1en, [
+        DMA1_STREAM0, DMA1_STREAM1, DMA1_STREAM2, DMA1_STREAM3,
+        DMA1_STREAM4, DMA1_STREAM5, DMA1_STREAM6, DMA1_STREAM7,
+    ];
+    DMA2, dma2rst, dma2en, [
+        DMA2_STREAM0, DMA2_STREAM1, DMA2_STREAM2, DMA2_STREAM3,
+        DMA2_STREAM4, DMA2_STREAM5, DMA2_STREAM6, DMA2_STREAM7,
+    ];
 );
 
 /// Used by [`Transfer::enable_interrupts`] to identify DMA interrupts
@@ -642,7 +791,7 @@ impl Default for Interrupts {
 }
 
 /// A DMA error
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub enum Error {
     Transfer,
     DirectMode,
@@ -1,7 +1,10 @@
 //! General Purpose Input / Output
 
+use core::convert::Infallible;
 use core::marker::PhantomData;
 
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+
 use crate::pac::{EXTI, RCC, SYSCFG};
 
 /// Extension trait to split a GPIO peripheral in independent pins and registers
@@ -87,6 +90,21 @@ pub trait ExtiPin {
     fn clear_interrupt_pending_bit(&mut self);
 }
 
+/// Temporarily drive an alternate function pin by hand
+///
+/// This is used by peripheral drivers that need to bit-bang their pins, e.g.
+/// for I2C bus recovery.
+pub trait WithOpenDrainOutput {
+    /// The pin, configured as an open drain output
+    type Output: OutputPin<Error = Infallible> + InputPin<Error = Infallible>;
+
+    /// Configures the pin as an open drain output, calls `f` with it, then
+    /// restores the previous pin configuration
+    fn with_open_drain_output<R, F>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self::Output) -> R;
+}
+
 macro_rules! gpio {
     ($GPIOX:ident, $gpiox:ident, $iopxenr:ident, $PXx:ident, $extigpionr:expr, [
         $($PXi:ident: ($pxi:ident, $i:expr, $MODE:ty, $exticri:ident),)+
@@ -103,7 +121,7 @@ macro_rules! gpio {
             use super::{
                 Alternate, Floating, GpioExt, Input, OpenDrain, Output, Speed,
                 PullDown, PullUp, PushPull, AF0, AF1, AF2, AF3, AF4, AF5, AF6, AF7, AF8, AF9, AF10,
-                AF11, AF12, AF13, AF14, AF15, Analog, Edge, ExtiPin,
+                AF11, AF12, AF13, AF14, AF15, Analog, Edge, ExtiPin, WithOpenDrainOutput,
             };
 
             /// GPIO parts
@@ -542,6 +560,49 @@ macro_rules! gpio {
                     }
                 }
 
+                impl<MODE> WithOpenDrainOutput for $PXi<Alternate<MODE>> {
+                    type Output = $PXi<Output<OpenDrain>>;
+
+                    fn with_open_drain_output<R, F>(&mut self, f: F) -> R
+                    where
+                        F: FnOnce(&mut Self::Output) -> R,
+                    {
+                        let offset = 2 * $i;
+
+                        // NOTE(unsafe) atomic read with no side effects
+                        let (moder, otyper) = unsafe {
+                            (
+                                (*$GPIOX::ptr()).moder.read().bits() & (0b11 << offset),
+                                (*$GPIOX::ptr()).otyper.read().bits() & (0b1 << $i),
+                            )
+                        };
+
+                        unsafe {
+                            // Release the line before switching to output mode
+                            (*$GPIOX::ptr()).bsrr.write(|w| w.bits(1 << $i));
+                            &(*$GPIOX::ptr()).otyper.modify(|r, w| {
+                                w.bits(r.bits() | (0b1 << $i))
+                            });
+                            &(*$GPIOX::ptr()).moder.modify(|r, w| {
+                                w.bits((r.bits() & !(0b11 << offset)) | (0b01 << offset))
+                            })
+                        };
+
+                        let result = f(&mut $PXi { _mode: PhantomData });
+
+                        unsafe {
+                            &(*$GPIOX::ptr()).otyper.modify(|r, w| {
+                                w.bits((r.bits() & !(0b1 << $i)) | otyper)
+                            });
+                            &(*$GPIOX::ptr()).moder.modify(|r, w| {
+                                w.bits((r.bits() & !(0b11 << offset)) | moder)
+                            })
+                        };
+
+                        result
+                    }
+                }
+
                 impl<MODE> $PXi<Alternate<MODE>> {
                     /// Turns pin alternate configuration pin into open drain
                     pub fn set_open_drain(self) -> Self {
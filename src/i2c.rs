@@ -1,18 +1,28 @@
 //! Inter-Integrated Circuit (I2C) bus
 //! For now, only master mode is implemented
+//!
+//! SMBus/PMBus host mode, with hardware PEC and bus timeouts, can be enabled
+//! through [`I2c::enable_smbus`].
+//!
+//! Besides the blocking API of [`BlockingI2c`], [`I2c`] provides interrupt
+//! driven transfers: start one with [`I2c::start_write`], [`I2c::start_read`]
+//! or [`I2c::start_write_read`], call [`I2c::handle_event_interrupt`] and
+//! [`I2c::handle_error_interrupt`] from the I2Cx_EV and I2Cx_ER interrupt
+//! handlers, and collect the result with [`I2c::poll`].
 
 // NB : this implementation started as a modified copy of https://github.com/stm32-rs/stm32f1xx-hal/blob/master/src/i2c.rs
 
 #[cfg_attr(test, allow(unused_imports))]
 use micromath::F32Ext;
 
-use crate::gpio::gpioa::PA8;
-use crate::gpio::gpiob::{PB10, PB11, PB6, PB7, PB8, PB9};
+use crate::gpio::gpioa::{PA8, PA9};
+use crate::gpio::gpiob::{PB10, PB11, PB12, PB5, PB6, PB7, PB8, PB9};
 use crate::gpio::gpioc::PC9;
-use crate::gpio::gpiof::{PF0, PF1};
-use crate::gpio::gpioh::{PH4, PH5, PH7, PH8};
-use crate::gpio::{Alternate, AF4};
+use crate::gpio::gpiof::{PF0, PF1, PF2};
+use crate::gpio::gpioh::{PH4, PH5, PH6, PH7, PH8, PH9};
+use crate::gpio::{Alternate, WithOpenDrainOutput, AF4};
 use crate::hal::blocking::i2c::{Read, Write, WriteRead};
+use crate::hal::digital::v2::{InputPin, OutputPin};
 use crate::pac::{DWT, I2C1, I2C2, I2C3};
 use crate::rcc::{sealed::RccBus, Clocks, Enable, GetBusFreq, Reset};
 use crate::time::Hertz;
@@ -20,9 +30,12 @@ use nb::Error::{Other, WouldBlock};
 use nb::{Error as NbError, Result as NbResult};
 
 use cast::u16;
+use core::convert::Infallible;
+use core::mem;
+use cortex_m::asm;
 
 /// I2C error
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Error {
     /// Bus error
     Bus,
@@ -34,9 +47,15 @@ pub enum Error {
     Overrun,
     /// Bus is busy
     Busy,
-    // Pec, // SMBUS mode only
-    // Timeout, // SMBUS mode only
-    // Alert, // SMBUS mode only
+    /// PEC mismatch on reception (SMBus mode only)
+    Pec,
+    /// Clock low or cumulative clock extension timeout (SMBus mode only)
+    Timeout,
+    /// SMBALERT# was asserted (SMBus mode only)
+    ///
+    /// An alert doesn't affect ongoing transfers, so it is only reported by
+    /// `handle_error_interrupt`. Otherwise, poll `take_smbus_alert`.
+    Alert,
     #[doc(hidden)]
     _Extensible,
 }
@@ -77,12 +96,54 @@ impl Mode {
     }
 }
 
+/// SMBus/PMBus host configuration, see [`I2c::enable_smbus`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SmbusConfig {
+    /// Append a Packet Error Checking byte to every transfer, and check it on
+    /// reception
+    pub pec: bool,
+    /// Clock low timeout (TIMEOUTA), in microseconds. The SMBus specification
+    /// requires this to be between 25 and 35 ms.
+    pub timeout_a_us: Option<u32>,
+    /// Cumulative clock low extension timeout (TIMEOUTB), in microseconds.
+    /// This is tLOW:MEXT in the SMBus specification (10 ms).
+    pub timeout_b_us: Option<u32>,
+}
+
+impl Default for SmbusConfig {
+    fn default() -> Self {
+        SmbusConfig {
+            pec: true,
+            timeout_a_us: Some(25_000),
+            timeout_b_us: None,
+        }
+    }
+}
+
+/// Converts a timeout in microseconds into a TIMEOUTA/TIMEOUTB value
+///
+/// The timeout is `(TIMEOUTx + 1) * 2048 * t_I2CCLK`. The result saturates at
+/// the 12-bit maximum of the register field.
+fn smbus_timeout_bits(pclk: u32, timeout_us: u32) -> u16 {
+    let ticks = timeout_us as u64 * pclk as u64 / 1_000_000 / 2048;
+    if ticks == 0 {
+        0
+    } else if ticks > 0x1000 {
+        0xfff
+    } else {
+        ticks as u16 - 1
+    }
+}
+
 /// Marker trait to define SCL pins for an I2C interface.
 pub trait PinScl<I2C> {}
 
 /// Marker trait to define SDA pins for an I2C interface.
 pub trait PinSda<I2C> {}
 
+/// Marker trait to define SMBALERT# pins for an I2C interface.
+pub trait PinSmba<I2C> {}
+
 impl PinScl<I2C1> for PB6<Alternate<AF4>> {}
 impl PinScl<I2C1> for PB8<Alternate<AF4>> {}
 impl PinScl<I2C2> for PB10<Alternate<AF4>> {}
@@ -99,12 +160,73 @@ impl PinSda<I2C2> for PH5<Alternate<AF4>> {}
 impl PinSda<I2C3> for PC9<Alternate<AF4>> {}
 impl PinSda<I2C3> for PH8<Alternate<AF4>> {}
 
+impl PinSmba<I2C1> for PB5<Alternate<AF4>> {}
+impl PinSmba<I2C2> for PB12<Alternate<AF4>> {}
+impl PinSmba<I2C2> for PF2<Alternate<AF4>> {}
+impl PinSmba<I2C2> for PH6<Alternate<AF4>> {}
+impl PinSmba<I2C3> for PA9<Alternate<AF4>> {}
+impl PinSmba<I2C3> for PH9<Alternate<AF4>> {}
+
+/// Buffers handed back at the end of an interrupt driven transfer
+#[derive(Debug)]
+pub struct Buffers {
+    /// The bytes that were written
+    pub write: &'static [u8],
+    /// The buffer the received bytes were written to
+    pub read: &'static mut [u8],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum IrqState {
+    Idle,
+    Write,
+    Read,
+    Done(Result<(), Error>),
+}
+
+/// State of the interrupt driven transfer engine
+struct IrqTransfer {
+    state: IrqState,
+    addr: u8,
+    write: &'static [u8],
+    read: &'static mut [u8],
+    /// Bytes transferred in the current direction, including the PEC byte
+    pos: usize,
+    callback: Option<fn(Result<(), Error>)>,
+}
+
+impl IrqTransfer {
+    fn new() -> Self {
+        IrqTransfer {
+            state: IrqState::Idle,
+            addr: 0,
+            write: &[],
+            read: &mut [],
+            pos: 0,
+            callback: None,
+        }
+    }
+}
+
+/// Splits the remaining bytes of a transfer into an NBYTES value, and whether
+/// RELOAD is needed for the bytes that don't fit
+fn next_chunk(remaining: usize) -> (u8, bool) {
+    if remaining > 255 {
+        (255, true)
+    } else {
+        (remaining as u8, false)
+    }
+}
+
 /// I2C peripheral operating in master mode
 pub struct I2c<I2C, SCL, SDA> {
     i2c: I2C,
     pins: (SCL, SDA),
     mode: Mode,
     pclk: u32,
+    sysclk: u32,
+    pec: bool,
+    transfer: IrqTransfer,
 }
 
 /// embedded-hal compatible blocking I2C implementation
@@ -231,6 +353,51 @@ fn blocking_i2c<I2C, SCL, SDA>(
     };
 }
 
+/// Clocks SCL until the slave releases SDA, then generates a STOP condition
+///
+/// At most nine clock pulses are generated, which is enough for any slave to
+/// finish the byte it is sending. Returns whether SDA is released afterwards.
+fn clock_out_bus<SCL, SDA>(scl: &mut SCL, sda: &mut SDA, half_period: u32) -> bool
+where
+    SCL: OutputPin<Error = Infallible> + InputPin<Error = Infallible>,
+    SDA: OutputPin<Error = Infallible> + InputPin<Error = Infallible>,
+{
+    let _ = sda.set_high();
+    let _ = scl.set_high();
+    asm::delay(half_period);
+
+    for _ in 0..9 {
+        if sda.is_high() == Ok(true) {
+            break;
+        }
+
+        let _ = scl.set_low();
+        asm::delay(half_period);
+        let _ = scl.set_high();
+        asm::delay(half_period);
+
+        // Give a slave stretching the clock some time to release SCL
+        for _ in 0..10 {
+            if scl.is_high() == Ok(true) {
+                break;
+            }
+            asm::delay(half_period);
+        }
+    }
+
+    // STOP condition: SDA going high while SCL is high
+    let _ = scl.set_low();
+    asm::delay(half_period);
+    let _ = sda.set_low();
+    asm::delay(half_period);
+    let _ = scl.set_high();
+    asm::delay(half_period);
+    let _ = sda.set_high();
+    asm::delay(half_period);
+
+    sda.is_high() == Ok(true)
+}
+
 macro_rules! check_status_flag {
     ($i2c:expr, $flag:ident, $status:ident) => {{
         let isr = $i2c.isr.read();
@@ -247,6 +414,12 @@ macro_rules! check_status_flag {
         } else if isr.ovr().bit_is_set() {
             $i2c.icr.write(|w| w.stopcf().set_bit().ovrcf().set_bit());
             Err(Other(Error::Overrun))
+        } else if isr.pecerr().bit_is_set() {
+            $i2c.icr.write(|w| w.peccf().set_bit());
+            Err(Other(Error::Pec))
+        } else if isr.timeout().bit_is_set() {
+            $i2c.icr.write(|w| w.timoutcf().set_bit());
+            Err(Other(Error::Timeout))
         } else if isr.$flag().$status() {
             Ok(())
         } else {
@@ -297,10 +470,19 @@ macro_rules! hal {
                     $I2CX::reset(apb);
 
                     let pclk = <$I2CX as RccBus>::Bus::get_frequency(&clocks).0;
+                    let sysclk = clocks.sysclk().0;
 
                     assert!(mode.get_frequency().0 <= 400_000);
 
-                    let mut i2c = I2c { i2c, pins, mode, pclk };
+                    let mut i2c = I2c {
+                        i2c,
+                        pins,
+                        mode,
+                        pclk,
+                        sysclk,
+                        pec: false,
+                        transfer: IrqTransfer::new(),
+                    };
                     i2c.init();
                     i2c
                 }
@@ -364,14 +546,138 @@ macro_rules! hal {
                 }
 
                 /// Perform an I2C software reset
-                #[allow(dead_code)]
-                fn reset(&mut self) {
-                    self.i2c.cr1.write(|w| w.pe().disabled());
+                ///
+                /// This releases SCL and SDA, and resets the internal state
+                /// machine and status flags, while keeping the configuration.
+                /// Use it to recover from an arbitration loss or any other
+                /// error that left a transfer unfinished.
+                pub fn reset(&mut self) {
+                    self.i2c.cr1.modify(|_, w| w.pe().disabled());
                     // wait for disabled
                     while self.i2c.cr1.read().pe().is_enabled() {}
 
                     // Re-enable
-                    self.i2c.cr1.write(|w| w.pe().enabled());
+                    self.i2c.cr1.modify(|_, w| w.pe().enabled());
+                }
+
+                /// Recovers a bus on which a slave holds SDA low
+                ///
+                /// This happens when the master is reset in the middle of a
+                /// read. SCL and SDA are temporarily driven by hand as open
+                /// drain outputs: up to nine clock pulses are sent until the
+                /// slave releases SDA, followed by a STOP condition. The pins
+                /// are then switched back to their alternate function and the
+                /// peripheral is reset.
+                ///
+                /// Returns [`Error::Busy`] if SDA is still held low afterwards.
+                pub fn recover_bus(&mut self) -> Result<(), Error>
+                where
+                    SCL: WithOpenDrainOutput,
+                    SDA: WithOpenDrainOutput,
+                {
+                    self.i2c.cr1.modify(|_, w| w.pe().disabled());
+
+                    // Half a clock period at the configured bus frequency, in
+                    // core clock cycles
+                    let half_period = self.sysclk / self.mode.get_frequency().0 / 2;
+
+                    let (scl, sda) = &mut self.pins;
+                    let released = scl.with_open_drain_output(|scl| {
+                        sda.with_open_drain_output(|sda| clock_out_bus(scl, sda, half_period))
+                    });
+
+                    self.reset();
+
+                    if released {
+                        Ok(())
+                    } else {
+                        Err(Error::Busy)
+                    }
+                }
+
+                /// Enables SMBus/PMBus host mode
+                ///
+                /// With `config.pec` set, every transfer that ends with a STOP
+                /// condition is followed by a hardware generated PEC byte, and
+                /// the PEC byte of every read is checked by the peripheral. A
+                /// mismatch is reported as [`Error::Pec`], an expired timeout
+                /// as [`Error::Timeout`].
+                pub fn enable_smbus(&mut self, config: SmbusConfig) {
+                    // PECEN, SMBHEN and TIMEOUTR may only be written while the
+                    // peripheral is disabled
+                    self.i2c.cr1.modify(|_, w| w.pe().disabled());
+                    while self.i2c.cr1.read().pe().is_enabled() {}
+
+                    let pclk = self.pclk;
+                    self.i2c.timeoutr.write(|w| {
+                        let w = match config.timeout_a_us {
+                            Some(us) => w
+                                .timeouta().bits(smbus_timeout_bits(pclk, us))
+                                // Detect SCL low, not bus idle
+                                .tidle().clear_bit()
+                                .timouten().set_bit(),
+                            None => w.timouten().clear_bit(),
+                        };
+                        match config.timeout_b_us {
+                            Some(us) => w
+                                .timeoutb().bits(smbus_timeout_bits(pclk, us))
+                                .texten().set_bit(),
+                            None => w.texten().clear_bit(),
+                        }
+                    });
+
+                    self.i2c.cr1.modify(|_, w| {
+                        w.smbhen().set_bit()
+                            .pecen().bit(config.pec)
+                            .pe().enabled()
+                    });
+                    self.pec = config.pec;
+                }
+
+                /// Disables SMBus/PMBus host mode, PEC and the bus timeouts
+                pub fn disable_smbus(&mut self) {
+                    self.i2c.cr1.modify(|_, w| w.pe().disabled());
+                    while self.i2c.cr1.read().pe().is_enabled() {}
+
+                    self.i2c.timeoutr.write(|w| w.timouten().clear_bit().texten().clear_bit());
+                    self.i2c.cr1.modify(|_, w| {
+                        w.smbhen().clear_bit()
+                            .pecen().clear_bit()
+                            .alerten().clear_bit()
+                            .pe().enabled()
+                    });
+                    self.pec = false;
+                }
+
+                /// Enables detection of SMBALERT# on `smba`
+                ///
+                /// An alert sets the ALERT flag, which doesn't affect ongoing
+                /// transfers, and raises the I2Cx_ER interrupt. It is reported
+                /// as [`Error::Alert`] by
+                /// [`handle_error_interrupt`](I2c::handle_error_interrupt), or
+                /// can be checked and cleared with
+                /// [`take_smbus_alert`](I2c::take_smbus_alert). Otherwise,
+                /// the interrupt keeps firing.
+                pub fn enable_smbus_alert<SMBA>(&mut self, _smba: &SMBA)
+                where
+                    SMBA: PinSmba<$I2CX>,
+                {
+                    self.i2c.cr1.modify(|_, w| w.alerten().set_bit().errie().set_bit());
+                }
+
+                /// Disables detection of SMBALERT#
+                pub fn disable_smbus_alert(&mut self) {
+                    self.i2c.cr1.modify(|_, w| w.alerten().clear_bit());
+                }
+
+                /// Returns `true` and clears the flag if SMBALERT# was asserted
+                pub fn take_smbus_alert(&mut self) -> bool {
+                    if self.i2c.isr.read().alert().bit_is_set() {
+                        self.i2c.icr.write(|w| w.alertcf().set_bit());
+                        true
+                    } else {
+                        false
+                    }
                 }
 
                 /// Set (7-bit) slave address, bus direction (write or read),
@@ -381,16 +687,27 @@ macro_rules! hal {
                 /// read. The peripheral automatically waits for the bus to be
                 /// free before sending the START and address
                 ///
+                /// If PEC is enabled and the transfer ends with a STOP, one
+                /// more byte is added to the transfer for the PEC. As 255
+                /// bytes and the PEC byte don't fit into NBYTES, such a
+                /// transfer is started with RELOAD set, and has to be
+                /// continued with `reload(0)` once TCR is set.
+                ///
                 /// Data transfers of more than 255 bytes are not yet
                 /// supported, 10-bit slave address are not yet supported
                 fn start(&self, addr: u8, n_bytes: u8, read: bool, auto_stop: bool) {
+                    let split = self.pec && auto_stop && n_bytes == 255;
+                    let pec = self.pec && auto_stop && !split;
+
                     self.i2c.cr2.write(|mut w| {
                         // Setup data
                         w = w.sadd()
                             .bits(u16(addr << 1 | 0))
                             .add10().clear_bit()
                             .nbytes()
-                            .bits(n_bytes as u8)
+                            .bits(n_bytes + pec as u8)
+                            .pecbyte().bit(pec)
+                            .reload().bit(split)
                             .start()
                             .set_bit();
 
@@ -401,13 +718,335 @@ macro_rules! hal {
                         };
 
                         // setup auto-stop
-                        match auto_stop {
+                        match auto_stop && !split {
                             true => w.autoend().automatic(),
                             false => w.autoend().software(),
                         }
                     });
                 }
 
+                /// Generate a START condition for a read of `n_bytes` with
+                /// RELOAD set, so the length of the rest of the transfer can
+                /// be given later through `reload`
+                fn start_reload(&self, addr: u8, n_bytes: u8) {
+                    self.i2c.cr2.write(|w| {
+                        w.sadd()
+                            .bits(u16(addr << 1 | 0))
+                            .add10().clear_bit()
+                            .nbytes()
+                            .bits(n_bytes)
+                            .reload().set_bit()
+                            .rd_wrn().read()
+                            .start()
+                            .set_bit()
+                    });
+                }
+
+                /// Continue a transfer started with RELOAD set for `n_bytes`
+                /// more bytes, ending with an automatic STOP (and PEC, if
+                /// enabled). Must be called once TCR is set.
+                ///
+                /// If PEC is enabled and `n_bytes` is 255, RELOAD stays set,
+                /// and the PEC byte has to follow through `reload(0)`.
+                fn reload(&self, n_bytes: u8) {
+                    let split = self.pec && n_bytes == 255;
+                    let pec = self.pec && !split;
+
+                    self.i2c.cr2.modify(|_, w| {
+                        w.nbytes()
+                            .bits(n_bytes + pec as u8)
+                            .pecbyte().bit(pec)
+                            .reload().bit(split)
+                            .autoend().bit(!split)
+                    });
+                }
+
+                /// Starts an interrupt driven write of `bytes` to slave `addr`
+                ///
+                /// If `bytes` is empty, only the address is sent, followed by
+                /// a STOP. This can be used to probe for a slave, or as an
+                /// SMBus quick command.
+                ///
+                /// Returns [`Error::Busy`] if the previous transfer hasn't
+                /// finished, or its result hasn't been collected by
+                /// [`poll`](I2c::poll) yet.
+                pub fn start_write(&mut self, addr: u8, bytes: &'static [u8]) -> Result<(), Error> {
+                    self.start_transfer(addr, bytes, &mut [])
+                }
+
+                /// Starts an interrupt driven read from slave `addr` to fill
+                /// `buffer`
+                pub fn start_read(&mut self, addr: u8, buffer: &'static mut [u8]) -> Result<(), Error> {
+                    self.start_transfer(addr, &[], buffer)
+                }
+
+                /// Starts an interrupt driven write of `bytes` to slave
+                /// `addr`, followed by a repeated START and a read to fill
+                /// `buffer`
+                pub fn start_write_read(
+                    &mut self,
+                    addr: u8,
+                    bytes: &'static [u8],
+                    buffer: &'static mut [u8],
+                ) -> Result<(), Error> {
+                    self.start_transfer(addr, bytes, buffer)
+                }
+
+                /// Sets a function to be called from the interrupt handlers
+                /// when an interrupt driven transfer finishes
+                pub fn set_completion_callback(&mut self, callback: Option<fn(Result<(), Error>)>) {
+                    self.transfer.callback = callback;
+                }
+
+                /// Returns the buffers of a finished interrupt driven transfer
+                ///
+                /// Returns `WouldBlock` while the transfer is ongoing, or if
+                /// no transfer was started.
+                pub fn poll(&mut self) -> NbResult<Buffers, (Error, Buffers)> {
+                    let result = match self.transfer.state {
+                        IrqState::Done(result) => result,
+                        _ => return Err(WouldBlock),
+                    };
+
+                    self.transfer.state = IrqState::Idle;
+                    let buffers = Buffers {
+                        write: mem::take(&mut self.transfer.write),
+                        read: mem::take(&mut self.transfer.read),
+                    };
+
+                    match result {
+                        Ok(()) => Ok(buffers),
+                        Err(error) => Err(Other((error, buffers))),
+                    }
+                }
+
+                /// Drives an interrupt driven transfer. Call this from the
+                /// I2Cx_EV interrupt handler.
+                ///
+                /// Returns the result of the transfer once it has finished.
+                pub fn handle_event_interrupt(&mut self) -> Option<Result<(), Error>> {
+                    let isr = self.i2c.isr.read();
+
+                    match self.transfer.state {
+                        IrqState::Write => {
+                            let last = self.transfer.read.is_empty();
+
+                            if isr.nackf().bit_is_set() {
+                                self.i2c.icr.write(|w| w.nackcf().set_bit());
+                                // Without AUTOEND, STOP has to be generated
+                                // by software
+                                if !last {
+                                    self.i2c.cr2.modify(|_, w| w.stop().set_bit());
+                                }
+                                return self.finish_transfer(Err(Error::Acknowledge));
+                            }
+
+                            if isr.txis().bit_is_set() {
+                                let byte = self.transfer.write.get(self.transfer.pos).cloned();
+                                self.i2c.txdr.write(|w| w.txdata().bits(byte.unwrap_or(0)));
+                                self.transfer.pos += 1;
+                            } else if isr.tcr().bit_is_set() {
+                                let remaining = self.remaining(self.transfer.write.len(), last);
+                                self.reload_transfer(remaining, last);
+                            } else if isr.tc().bit_is_set() {
+                                // Write finished, reSTART for the read
+                                self.transfer.state = IrqState::Read;
+                                self.transfer.pos = 0;
+                                let remaining = self.remaining(self.transfer.read.len(), true);
+                                self.begin_transfer(true, remaining);
+                            } else if isr.stopf().bit_is_set() {
+                                self.i2c.icr.write(|w| w.stopcf().set_bit());
+                                return self.finish_transfer(Ok(()));
+                            }
+
+                            None
+                        }
+                        IrqState::Read => {
+                            if isr.nackf().bit_is_set() {
+                                // Address not acknowledged, STOP is generated
+                                // automatically
+                                self.i2c.icr.write(|w| w.nackcf().set_bit());
+                                return self.finish_transfer(Err(Error::Acknowledge));
+                            }
+
+                            if isr.rxne().is_not_empty() {
+                                let byte = self.i2c.rxdr.read().rxdata().bits();
+                                // The PEC byte doesn't go into the buffer
+                                if let Some(b) = self.transfer.read.get_mut(self.transfer.pos) {
+                                    *b = byte;
+                                }
+                                self.transfer.pos += 1;
+                            } else if isr.tcr().bit_is_set() {
+                                let remaining = self.remaining(self.transfer.read.len(), true);
+                                self.reload_transfer(remaining, true);
+                            } else if isr.stopf().bit_is_set() {
+                                self.i2c.icr.write(|w| w.stopcf().set_bit());
+                                return self.finish_transfer(Ok(()));
+                            }
+
+                            None
+                        }
+                        _ => {
+                            // Leftovers of a finished or aborted transfer
+                            self.i2c.icr.write(|w| w.stopcf().set_bit().nackcf().set_bit());
+                            None
+                        }
+                    }
+                }
+
+                /// Reports bus errors to an interrupt driven transfer. Call
+                /// this from the I2Cx_ER interrupt handler.
+                ///
+                /// Returns the result of the transfer if it was aborted by the
+                /// error. Errors that occur while no transfer is in progress,
+                /// as well as [`Error::Alert`], are returned without affecting
+                /// the transfer. After [`Error::Bus`] or
+                /// [`Error::Arbitration`], [`reset`](I2c::reset) the
+                /// peripheral.
+                pub fn handle_error_interrupt(&mut self) -> Option<Result<(), Error>> {
+                    let isr = self.i2c.isr.read();
+
+                    let error = if isr.berr().bit_is_set() {
+                        self.i2c.icr.write(|w| w.berrcf().set_bit());
+                        Error::Bus
+                    } else if isr.arlo().bit_is_set() {
+                        self.i2c.icr.write(|w| w.arlocf().set_bit());
+                        Error::Arbitration
+                    } else if isr.ovr().bit_is_set() {
+                        self.i2c.icr.write(|w| w.ovrcf().set_bit());
+                        Error::Overrun
+                    } else if isr.pecerr().bit_is_set() {
+                        self.i2c.icr.write(|w| w.peccf().set_bit());
+                        Error::Pec
+                    } else if isr.timeout().bit_is_set() {
+                        self.i2c.icr.write(|w| w.timoutcf().set_bit());
+                        Error::Timeout
+                    } else if isr.alert().bit_is_set() {
+                        self.i2c.icr.write(|w| w.alertcf().set_bit());
+                        return Some(Err(Error::Alert));
+                    } else {
+                        return None;
+                    };
+
+                    match self.transfer.state {
+                        IrqState::Write | IrqState::Read => self.finish_transfer(Err(error)),
+                        _ => Some(Err(error)),
+                    }
+                }
+
+                fn start_transfer(
+                    &mut self,
+                    addr: u8,
+                    bytes: &'static [u8],
+                    buffer: &'static mut [u8],
+                ) -> Result<(), Error> {
+                    if self.transfer.state != IrqState::Idle {
+                        return Err(Error::Busy);
+                    }
+                    let write_len = bytes.len();
+                    let last = buffer.is_empty();
+
+                    self.transfer.addr = addr;
+                    self.transfer.write = bytes;
+                    self.transfer.read = buffer;
+                    self.transfer.pos = 0;
+
+                    // Clear leftovers of a previous transfer
+                    self.i2c.icr.write(|w| w.stopcf().set_bit().nackcf().set_bit());
+
+                    if write_len > 0 || last {
+                        self.transfer.state = IrqState::Write;
+                        // A zero-byte write only consists of the address, so
+                        // there's no PEC byte either
+                        let remaining = match write_len {
+                            0 => 0,
+                            _ => self.remaining(write_len, last),
+                        };
+                        self.begin_transfer(false, remaining);
+                    } else {
+                        self.transfer.state = IrqState::Read;
+                        let remaining = self.remaining(self.transfer.read.len(), true);
+                        self.begin_transfer(true, remaining);
+                    }
+
+                    self.i2c.cr1.modify(|_, w| {
+                        w.txie().set_bit()
+                            .rxie().set_bit()
+                            .tcie().set_bit()
+                            .stopie().set_bit()
+                            .nackie().set_bit()
+                            .errie().set_bit()
+                    });
+
+                    Ok(())
+                }
+
+                /// Bytes left in the current direction of an interrupt driven
+                /// transfer, including the PEC byte if this direction ends it
+                fn remaining(&self, len: usize, last: bool) -> usize {
+                    len + (self.pec && last) as usize - self.transfer.pos
+                }
+
+                /// Generate a (repeated) START for the next direction of an
+                /// interrupt driven transfer. Writes that are followed by a
+                /// read don't end with a STOP.
+                fn begin_transfer(&self, read: bool, remaining: usize) {
+                    let (n_bytes, reload) = next_chunk(remaining);
+                    let last = read || self.transfer.read.is_empty();
+                    let pec = self.pec && last && !reload && n_bytes > 0;
+                    let addr = self.transfer.addr;
+
+                    self.i2c.cr2.write(|w| {
+                        let w = w.sadd()
+                            .bits(u16(addr << 1 | 0))
+                            .add10().clear_bit()
+                            .nbytes().bits(n_bytes)
+                            .reload().bit(reload)
+                            .autoend().bit(last)
+                            .pecbyte().bit(pec);
+
+                        let w = match read {
+                            true => w.rd_wrn().read(),
+                            false => w.rd_wrn().write(),
+                        };
+
+                        w.start().set_bit()
+                    });
+                }
+
+                /// Program the next chunk of an interrupt driven transfer,
+                /// once TCR is set
+                fn reload_transfer(&self, remaining: usize, last: bool) {
+                    let (n_bytes, reload) = next_chunk(remaining);
+                    let pec = self.pec && last && !reload;
+
+                    self.i2c.cr2.modify(|_, w| {
+                        w.nbytes().bits(n_bytes)
+                            .reload().bit(reload)
+                            .autoend().bit(last)
+                            .pecbyte().bit(pec)
+                    });
+                }
+
+                fn finish_transfer(&mut self, result: Result<(), Error>) -> Option<Result<(), Error>> {
+                    self.i2c.cr1.modify(|r, w| {
+                        w.txie().clear_bit()
+                            .rxie().clear_bit()
+                            .tcie().clear_bit()
+                            .stopie().clear_bit()
+                            .nackie().clear_bit()
+                            // Keep reporting SMBALERT#, if enabled
+                            .errie().bit(r.alerten().bit_is_set())
+                    });
+                    self.transfer.state = IrqState::Done(result);
+
+                    if let Some(callback) = self.transfer.callback {
+                        callback(result);
+                    }
+
+                    Some(result)
+                }
+
                 /// Releases the I2C peripheral and associated pins
                 pub fn free(self) -> ($I2CX, (SCL, SDA)) {
                     (self.i2c, self.pins)
@@ -456,8 +1095,201 @@ macro_rules! hal {
                 }
 
                 /// Wait for any previous address sequence to end automatically.
-                fn wait_start(&self) {
-                    while self.nb.i2c.cr2.read().start().bit_is_set() {};
+                ///
+                /// Returns [`Error::Busy`] if the bus doesn't become free
+                /// within the data timeout.
+                fn wait_start(&self) -> NbResult<(), Error> {
+                    let res: NbResult<(), Error> = busy_wait_cycles!(
+                        if self.nb.i2c.cr2.read().start().bit_is_set() {
+                            Err(WouldBlock)
+                        } else {
+                            Ok(())
+                        },
+                        self.data_timeout
+                    );
+
+                    res.map_err(|_| Other(Error::Busy))
+                }
+
+                /// Perform an I2C software reset, see [`I2c::reset`]
+                pub fn reset(&mut self) {
+                    self.nb.reset();
+                }
+
+                /// Recovers a bus on which a slave holds SDA low, see
+                /// [`I2c::recover_bus`]
+                pub fn recover_bus(&mut self) -> Result<(), Error>
+                where
+                    SCL: WithOpenDrainOutput,
+                    SDA: WithOpenDrainOutput,
+                {
+                    self.nb.recover_bus()
+                }
+
+                /// If `n_bytes` and the PEC byte didn't fit into one chunk,
+                /// wait for the data to be transferred and continue with the
+                /// PEC byte
+                fn wait_pec_reload(&self, n_bytes: usize) -> NbResult<(), Error> {
+                    if !self.nb.pec || n_bytes != 255 {
+                        return Ok(());
+                    }
+
+                    busy_wait_cycles!(
+                        check_status_flag!(self.nb.i2c, tcr, bit_is_set),
+                        self.data_timeout
+                    )?;
+                    self.nb.reload(0);
+
+                    Ok(())
+                }
+
+                /// If PEC is enabled, wait for the PEC byte that ends a read
+                /// and check that it matched
+                fn wait_pec_read(&self) -> NbResult<(), Error> {
+                    if !self.nb.pec {
+                        return Ok(());
+                    }
+
+                    // The PEC byte is received into RXDR like any other byte
+                    self.wait_byte_read()?;
+
+                    if self.nb.i2c.isr.read().pecerr().bit_is_set() {
+                        self.nb.i2c.icr.write(|w| w.peccf().set_bit());
+                        return Err(Other(Error::Pec));
+                    }
+
+                    Ok(())
+                }
+
+                /// SMBus "Send Byte": writes `byte` without a command code
+                pub fn smbus_send_byte(&mut self, addr: u8, byte: u8) -> NbResult<(), Error> {
+                    self.write(addr, &[byte])
+                }
+
+                /// SMBus "Receive Byte": reads a byte without a command code
+                pub fn smbus_receive_byte(&mut self, addr: u8) -> NbResult<u8, Error> {
+                    let mut buffer = [0];
+                    self.read(addr, &mut buffer)?;
+                    Ok(buffer[0])
+                }
+
+                /// SMBus "Write Byte": writes `byte` to register `command`
+                pub fn smbus_write_byte(
+                    &mut self,
+                    addr: u8,
+                    command: u8,
+                    byte: u8,
+                ) -> NbResult<(), Error> {
+                    self.write(addr, &[command, byte])
+                }
+
+                /// SMBus "Read Byte": reads a byte from register `command`
+                pub fn smbus_read_byte(&mut self, addr: u8, command: u8) -> NbResult<u8, Error> {
+                    let mut buffer = [0];
+                    self.write_read(addr, &[command], &mut buffer)?;
+                    Ok(buffer[0])
+                }
+
+                /// SMBus "Write Word": writes `word` (low byte first) to
+                /// register `command`
+                pub fn smbus_write_word(
+                    &mut self,
+                    addr: u8,
+                    command: u8,
+                    word: u16,
+                ) -> NbResult<(), Error> {
+                    self.write(addr, &[command, word as u8, (word >> 8) as u8])
+                }
+
+                /// SMBus "Read Word": reads a word (low byte first) from
+                /// register `command`
+                pub fn smbus_read_word(&mut self, addr: u8, command: u8) -> NbResult<u16, Error> {
+                    let mut buffer = [0; 2];
+                    self.write_read(addr, &[command], &mut buffer)?;
+                    Ok(u16::from(buffer[0]) | u16::from(buffer[1]) << 8)
+                }
+
+                /// SMBus "Block Write": writes the byte count followed by
+                /// `data` to register `command`
+                ///
+                /// `data.len()` must be less or equal than 253
+                pub fn smbus_block_write(
+                    &mut self,
+                    addr: u8,
+                    command: u8,
+                    data: &[u8],
+                ) -> NbResult<(), Error> {
+                    assert!(data.len() <= 253);
+
+                    let mut buffer = [0; 255];
+                    buffer[0] = command;
+                    buffer[1] = data.len() as u8;
+                    buffer[2..data.len() + 2].copy_from_slice(data);
+
+                    self.write(addr, &buffer[..data.len() + 2])
+                }
+
+                /// SMBus "Block Read": reads a block from register `command`
+                /// into `buffer`
+                ///
+                /// Returns the byte count sent by the slave. If it is larger
+                /// than `buffer`, the transfer is still completed, but the
+                /// bytes that don't fit are discarded.
+                pub fn smbus_block_read(
+                    &mut self,
+                    addr: u8,
+                    command: u8,
+                    buffer: &mut [u8],
+                ) -> NbResult<usize, Error> {
+                    self.wait_start()?;
+                    self.nb.start(addr, 1, false, false);
+                    self.wait_byte_write(command)?;
+
+                    busy_wait_cycles!(
+                        check_status_flag!(self.nb.i2c, tc, is_complete),
+                        self.data_timeout
+                    )?;
+
+                    // reSTART and read the byte count, keeping SCL stretched
+                    // afterwards until we know the length of the block
+                    self.nb.start_reload(addr, 1);
+                    let count = self.wait_byte_read()?;
+
+                    busy_wait_cycles!(
+                        check_status_flag!(self.nb.i2c, tcr, bit_is_set),
+                        self.data_timeout
+                    )?;
+
+                    self.nb.reload(count);
+
+                    for i in 0..count as usize {
+                        let byte = self.wait_byte_read()?;
+                        if let Some(b) = buffer.get_mut(i) {
+                            *b = byte;
+                        }
+                    }
+                    self.wait_pec_reload(count as usize)?;
+                    self.wait_pec_read()?;
+                    // automatic STOP
+
+                    Ok(count as usize)
+                }
+
+                /// SMBus "Process Call": writes `word` to register `command`
+                /// and reads back a word
+                pub fn smbus_process_call(
+                    &mut self,
+                    addr: u8,
+                    command: u8,
+                    word: u16,
+                ) -> NbResult<u16, Error> {
+                    let mut buffer = [0; 2];
+                    self.write_read(
+                        addr,
+                        &[command, word as u8, (word >> 8) as u8],
+                        &mut buffer,
+                    )?;
+                    Ok(u16::from(buffer[0]) | u16::from(buffer[1]) << 8)
                 }
             }
 
@@ -473,7 +1305,7 @@ macro_rules! hal {
                     // Wait for any previous address sequence to end
                     // automatically. This could be up to 50% of a bus
                     // cycle (ie. up to 0.5/freq)
-                    self.wait_start();
+                    self.wait_start()?;
 
                     // Set START and prepare to send `bytes`. The
                     // START bit can be set even if the bus is BUSY or
@@ -483,6 +1315,7 @@ macro_rules! hal {
                     for byte in bytes {
                         self.wait_byte_write(*byte)?;
                     }
+                    self.wait_pec_reload(bytes.len())?;
                     // automatic STOP
 
                     Ok(())
@@ -500,16 +1333,19 @@ macro_rules! hal {
                     // Wait for any previous address sequence to end
                     // automatically. This could be up to 50% of a bus
                     // cycle (ie. up to 0.5/freq)
-                    self.wait_start();
+                    self.wait_start()?;
 
                     // Set START and prepare to receive bytes into
                     // `buffer`. The START bit can be set even if the bus
                     // is BUSY or I2C is in slave mode.
                     self.nb.start(addr, buffer.len() as u8, true, true);
 
+                    let len = buffer.len();
                     for byte in buffer {
                         *byte = self.wait_byte_read()?;
                     }
+                    self.wait_pec_reload(len)?;
+                    self.wait_pec_read()?;
 
                     // automatic STOP
 
@@ -531,7 +1367,7 @@ macro_rules! hal {
                     assert!(buffer.len() < 256 && buffer.len() > 0);
 
                     // Start and make sure we don't send STOP after the write
-                    self.wait_start();
+                    self.wait_start()?;
                     self.nb.start(addr, bytes.len() as u8, false, false);
 
                     for byte in bytes {
@@ -548,9 +1384,12 @@ macro_rules! hal {
                     // reSTART and prepare to receive bytes into `buffer`
                     self.nb.start(addr, buffer.len() as u8, true, true);
 
+                    let len = buffer.len();
                     for byte in buffer {
                         *byte = self.wait_byte_read()?;
                     }
+                    self.wait_pec_reload(len)?;
+                    self.wait_pec_read()?;
                     // automatic STOP
 
                     Ok(())
@@ -0,0 +1,953 @@
+//! Interface to the SPI peripheral in I2S mode
+//!
+//! SPI1, SPI2 and SPI3 can be used as I2S interfaces. See section 32.7 in the
+//! STM32F746 Reference Manual.
+//!
+//! In master mode, the I2S clock is generated by PLLI2S, which needs to be
+//! enabled using [`CFGR::plli2s`](crate::rcc::CFGR::plli2s).
+//!
+//! The data register is 16 bits wide. 16-bit samples take one half-word each.
+//! 24-bit and 32-bit samples take two half-words each, most significant half
+//! first. 24-bit samples are left-aligned, the lowest 8 bits of the second
+//! half-word are ignored. For all standards except PCM, the left channel
+//! comes first.
+
+use core::{marker::PhantomData, ops::DerefMut, pin::Pin, slice};
+
+use as_slice::{AsMutSlice, AsSlice as _};
+
+use crate::{
+    dma,
+    gpio::{self, Alternate, AF5, AF6, AF7},
+    pac,
+    rcc::{Clocks, Rcc},
+    spi::{Rx, Tx},
+    state,
+    time::Hertz,
+};
+
+/// Entry point to the I2S API
+pub struct I2s<I, P, State> {
+    i2s: I,
+    pins: P,
+    _state: State,
+}
+
+/// The enabled I2S peripheral and the actual sample rate returned by
+/// [`I2s::enable_master`]
+pub type EnabledMaster<I, P, Dir> = (I2s<I, P, Enabled<Dir>>, Hertz);
+
+impl<I, P> I2s<I, P, state::Disabled>
+where
+    I: Instance,
+    P: Pins<I>,
+{
+    /// Create a new instance of the I2S API
+    pub fn new(instance: I, pins: P) -> Self {
+        Self {
+            i2s: instance,
+            pins,
+            _state: state::Disabled,
+        }
+    }
+
+    /// Initialize the I2S peripheral in master mode
+    ///
+    /// The peripheral generates CK and WS, as well as MCK, if an MCK pin has
+    /// been passed to [`I2s::new`]. Returns the peripheral together with the
+    /// sample rate that could actually be reached from the PLLI2S output.
+    ///
+    /// Returns [`Error::ClockDisabled`], if PLLI2S hasn't been enabled, and
+    /// [`Error::SampleRate`], if `sample_rate` is out of range for its output
+    /// frequency.
+    pub fn enable_master<Dir>(
+        self,
+        rcc: &mut Rcc,
+        clocks: &Clocks,
+        sample_rate: Hertz,
+        config: Config,
+    ) -> Result<EnabledMaster<I, P, Dir>, Error>
+    where
+        Dir: Direction,
+    {
+        let i2s_clock = clocks.plli2sclk().ok_or(Error::ClockDisabled)?;
+        let (div, odd, sample_rate) = calculate_divider(
+            i2s_clock.0,
+            sample_rate.0,
+            config.data_format.channel_32(),
+            P::MCK,
+        )
+        .ok_or(Error::SampleRate)?;
+
+        self.i2s.enable_clock(rcc);
+        self.i2s.configure(Settings {
+            config,
+            master: true,
+            transmit: Dir::TRANSMIT,
+            div,
+            odd,
+            mck: P::MCK,
+        });
+
+        let i2s = I2s {
+            i2s: self.i2s,
+            pins: self.pins,
+            _state: Enabled(PhantomData),
+        };
+        Ok((i2s, Hertz(sample_rate)))
+    }
+
+    /// Initialize the I2S peripheral in slave mode
+    ///
+    /// CK and WS are provided by the master.
+    pub fn enable_slave<Dir>(self, rcc: &mut Rcc, config: Config) -> I2s<I, P, Enabled<Dir>>
+    where
+        Dir: Direction,
+    {
+        self.i2s.enable_clock(rcc);
+        self.i2s.configure(Settings {
+            config,
+            master: false,
+            transmit: Dir::TRANSMIT,
+            div: 2,
+            odd: false,
+            mck: false,
+        });
+
+        I2s {
+            i2s: self.i2s,
+            pins: self.pins,
+            _state: Enabled(PhantomData),
+        }
+    }
+}
+
+impl<I, P, Dir> I2s<I, P, Enabled<Dir>>
+where
+    I: Instance,
+    P: Pins<I>,
+    Dir: Direction,
+{
+    /// Returns the channel the next half-word belongs to
+    ///
+    /// This is not meaningful in PCM mode.
+    pub fn channel(&self) -> Channel {
+        self.i2s.channel()
+    }
+
+    /// Disables the I2S peripheral
+    ///
+    /// In transmit mode, waits until the last half-word has been sent.
+    pub fn disable(self) -> I2s<I, P, state::Disabled> {
+        self.i2s.disable(Dir::TRANSMIT);
+
+        I2s {
+            i2s: self.i2s,
+            pins: self.pins,
+            _state: state::Disabled,
+        }
+    }
+}
+
+impl<I, P> I2s<I, P, Enabled<Transmit>>
+where
+    I: Instance,
+    P: Pins<I>,
+{
+    /// Sends a half-word
+    pub fn send(&mut self, half_word: u16) -> nb::Result<(), Error> {
+        self.i2s.send(half_word)
+    }
+
+    /// Sends all half-words in `data`, blocking until they have been written
+    pub fn write(&mut self, data: &[u16]) -> Result<(), Error> {
+        for &half_word in data {
+            nb::block!(self.i2s.send(half_word))?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends the data in `buffer` continuously, using circular DMA
+    ///
+    /// The buffer is split into two halves. While one half is being sent, the
+    /// other one can be refilled using [`CircularTx::next_half`]. The given
+    /// DMA interrupts are enabled, which is useful to refill the buffer from
+    /// the half transfer and transfer complete interrupts.
+    ///
+    /// DMA supports buffers up to 65535 half-words. If `buffer` is longer, or
+    /// if its length is odd, this method will panic.
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn write_circular<B, S>(
+        self,
+        buffer: Pin<B>,
+        dma: &dma::Handle<S::Instance, state::Enabled>,
+        stream: S,
+        interrupts: dma::Interrupts,
+    ) -> CircularTx<I, P, S, B>
+    where
+        Tx<I>: dma::Target<S>,
+        S: dma::Stream,
+        B: DerefMut + 'static,
+        B::Target: AsMutSlice<Element = u16>,
+    {
+        assert!(buffer.as_slice().len() % 2 == 0);
+
+        let address = self.i2s.dr_address();
+
+        // Safe, because the trait bounds on this method guarantee that
+        // `buffer` can be read from safely.
+        let mut transfer = unsafe {
+            dma::Transfer::new(
+                dma,
+                stream,
+                buffer,
+                Tx(PhantomData),
+                address,
+                dma::Direction::MemoryToPeripheral,
+            )
+        };
+        transfer.enable_circular(dma);
+        transfer.enable_interrupts(dma, interrupts);
+
+        CircularTx {
+            transfer: transfer.start(dma),
+            i2s: self,
+        }
+    }
+}
+
+impl<I, P> I2s<I, P, Enabled<Receive>>
+where
+    I: Instance,
+    P: Pins<I>,
+{
+    /// Receives a half-word
+    pub fn receive(&mut self) -> nb::Result<u16, Error> {
+        self.i2s.read()
+    }
+
+    /// Fills `buffer` with received half-words, blocking until it is full
+    pub fn read(&mut self, buffer: &mut [u16]) -> Result<(), Error> {
+        for half_word in buffer {
+            *half_word = nb::block!(self.i2s.read())?;
+        }
+
+        Ok(())
+    }
+
+    /// Receives data continuously into `buffer`, using circular DMA
+    ///
+    /// The buffer is split into two halves. While one half is being filled,
+    /// the other one can be processed using [`CircularRx::next_half`]. The
+    /// given DMA interrupts are enabled, which is useful to process the
+    /// buffer from the half transfer and transfer complete interrupts.
+    ///
+    /// DMA supports buffers up to 65535 half-words. If `buffer` is longer, or
+    /// if its length is odd, this method will panic.
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn read_circular<B, S>(
+        self,
+        buffer: Pin<B>,
+        dma: &dma::Handle<S::Instance, state::Enabled>,
+        stream: S,
+        interrupts: dma::Interrupts,
+    ) -> CircularRx<I, P, S, B>
+    where
+        Rx<I>: dma::Target<S>,
+        S: dma::Stream,
+        B: DerefMut + 'static,
+        B::Target: AsMutSlice<Element = u16>,
+    {
+        assert!(buffer.as_slice().len() % 2 == 0);
+
+        let address = self.i2s.dr_address();
+
+        // Safe, because the trait bounds on this method guarantee that
+        // `buffer` can be written to safely.
+        let mut transfer = unsafe {
+            dma::Transfer::new(
+                dma,
+                stream,
+                buffer,
+                Rx(PhantomData),
+                address,
+                dma::Direction::PeripheralToMemory,
+            )
+        };
+        transfer.enable_circular(dma);
+        transfer.enable_interrupts(dma, interrupts);
+
+        CircularRx {
+            transfer: transfer.start(dma),
+            i2s: self,
+        }
+    }
+}
+
+impl<I, P, State> I2s<I, P, State> {
+    /// Destroy the peripheral API and return a raw I2S peripheral instance
+    ///
+    /// The pins are also returned, to be used for other purposes.
+    pub fn free(self) -> (I, P) {
+        (self.i2s, self.pins)
+    }
+}
+
+/// Continuous I2S transmission from a circular DMA buffer
+///
+/// Created by [`I2s::write_circular`].
+pub struct CircularTx<I, P, S, B>
+where
+    Tx<I>: dma::Target<S>,
+    S: dma::Stream,
+{
+    transfer: dma::Transfer<Tx<I>, S, B, dma::Started>,
+    i2s: I2s<I, P, Enabled<Transmit>>,
+}
+
+impl<I, P, S, B> CircularTx<I, P, S, B>
+where
+    I: Instance,
+    Tx<I>: dma::Target<S>,
+    S: dma::Stream,
+    B: DerefMut,
+    B::Target: AsMutSlice<Element = u16>,
+{
+    /// Passes the half of the buffer that has just been sent to `f`, to be
+    /// refilled
+    ///
+    /// Returns `WouldBlock`, if no half has been sent since the last call.
+    /// Returns [`Error::Missed`], if both halves have been sent in the
+    /// meantime, which means that stale data has been sent.
+    pub fn next_half<F>(
+        &mut self,
+        dma: &dma::Handle<S::Instance, state::Enabled>,
+        f: F,
+    ) -> nb::Result<(), Error>
+    where
+        F: FnOnce(&mut [u16]),
+    {
+        self.i2s.i2s.check_errors()?;
+        next_half(&self.transfer, dma, f)
+    }
+
+    /// Stops the transfer
+    ///
+    /// The I2S peripheral is still enabled afterwards, and the DMA resources
+    /// are returned so they can be used again.
+    pub fn stop(
+        self,
+        dma: &dma::Handle<S::Instance, state::Enabled>,
+    ) -> CircularTxResources<I, P, S, B> {
+        let (res, _) = self.transfer.stop(dma);
+        (self.i2s, res)
+    }
+}
+
+/// The I2S peripheral and DMA resources returned by [`CircularTx::stop`]
+pub type CircularTxResources<I, P, S, B> = (
+    I2s<I, P, Enabled<Transmit>>,
+    dma::TransferResources<Tx<I>, S, B>,
+);
+
+/// Continuous I2S reception into a circular DMA buffer
+///
+/// Created by [`I2s::read_circular`].
+pub struct CircularRx<I, P, S, B>
+where
+    Rx<I>: dma::Target<S>,
+    S: dma::Stream,
+{
+    transfer: dma::Transfer<Rx<I>, S, B, dma::Started>,
+    i2s: I2s<I, P, Enabled<Receive>>,
+}
+
+impl<I, P, S, B> CircularRx<I, P, S, B>
+where
+    I: Instance,
+    Rx<I>: dma::Target<S>,
+    S: dma::Stream,
+    B: DerefMut,
+    B::Target: AsMutSlice<Element = u16>,
+{
+    /// Passes the half of the buffer that has just been filled to `f`
+    ///
+    /// Returns `WouldBlock`, if no half has been filled since the last call.
+    /// Returns [`Error::Missed`], if both halves have been filled in the
+    /// meantime, which means that data has been lost.
+    pub fn next_half<F>(
+        &mut self,
+        dma: &dma::Handle<S::Instance, state::Enabled>,
+        f: F,
+    ) -> nb::Result<(), Error>
+    where
+        F: FnOnce(&mut [u16]),
+    {
+        self.i2s.i2s.check_errors()?;
+        next_half(&self.transfer, dma, f)
+    }
+
+    /// Stops the transfer
+    ///
+    /// The I2S peripheral is still enabled afterwards, and the DMA resources
+    /// are returned so they can be used again.
+    pub fn stop(
+        self,
+        dma: &dma::Handle<S::Instance, state::Enabled>,
+    ) -> CircularRxResources<I, P, S, B> {
+        let (res, _) = self.transfer.stop(dma);
+        (self.i2s, res)
+    }
+}
+
+/// The I2S peripheral and DMA resources returned by [`CircularRx::stop`]
+pub type CircularRxResources<I, P, S, B> = (
+    I2s<I, P, Enabled<Receive>>,
+    dma::TransferResources<Rx<I>, S, B>,
+);
+
+fn next_half<T, S, B, F>(
+    transfer: &dma::Transfer<T, S, B, dma::Started>,
+    dma: &dma::Handle<S::Instance, state::Enabled>,
+    f: F,
+) -> nb::Result<(), Error>
+where
+    T: dma::Target<S>,
+    S: dma::Stream,
+    B: DerefMut,
+    B::Target: AsMutSlice<Element = u16>,
+    F: FnOnce(&mut [u16]),
+{
+    transfer.check_error(dma).map_err(Error::Dma)?;
+
+    let first = transfer.take_half_transfer(dma);
+    let second = transfer.take_transfer_complete(dma);
+
+    let half = transfer.len() / 2;
+    let offset = match (first, second) {
+        (false, false) => return Err(nb::Error::WouldBlock),
+        (true, false) => 0,
+        (false, true) => half,
+        (true, true) => return Err(nb::Error::Other(Error::Missed)),
+    };
+
+    // NOTE(unsafe) The DMA stream has moved on to the other half of the
+    // buffer, so it doesn't access this half until it wraps around.
+    let buffer = unsafe {
+        let ptr = transfer.buffer().as_slice().as_ptr() as *mut u16;
+        slice::from_raw_parts_mut(ptr.add(offset), half)
+    };
+    f(buffer);
+
+    Ok(())
+}
+
+/// Calculates the I2S prescaler for the given sample rate
+///
+/// Returns I2SDIV, ODD, and the sample rate that is actually reached, or
+/// `None`, if the sample rate is out of range.
+fn calculate_divider(
+    i2s_clock: u32,
+    sample_rate: u32,
+    channel_32: bool,
+    mck: bool,
+) -> Option<(u8, bool, u32)> {
+    // Number of I2S clock cycles per frame, before the prescaler
+    let frame = if mck {
+        256
+    } else if channel_32 {
+        64
+    } else {
+        32
+    };
+
+    if sample_rate == 0 {
+        return None;
+    }
+
+    // The prescaler is `2 * I2SDIV + ODD`. Round it to the nearest value.
+    let frame_clock = frame as u64 * sample_rate as u64;
+    let prescaler = (i2s_clock as u64 + frame_clock / 2) / frame_clock;
+    if !(4..=511).contains(&prescaler) {
+        return None;
+    }
+
+    let prescaler = prescaler as u32;
+    Some((
+        (prescaler / 2) as u8,
+        prescaler % 2 == 1,
+        i2s_clock / (frame * prescaler),
+    ))
+}
+
+/// Implemented for all instances of the SPI peripheral that support I2S
+///
+/// Users of this crate should not implement this trait.
+pub trait Instance {
+    fn enable_clock(&self, rcc: &mut Rcc);
+    fn configure(&self, settings: Settings);
+    fn check_errors(&self) -> Result<(), Error>;
+    fn read(&self) -> nb::Result<u16, Error>;
+    fn send(&self, half_word: u16) -> nb::Result<(), Error>;
+    fn channel(&self) -> Channel;
+    fn dr_address(&self) -> u32;
+    fn disable(&self, transmit: bool);
+}
+
+/// The I2S configuration, as passed to [`Instance::configure`]
+///
+/// Users of this crate should not need to use this struct directly.
+pub struct Settings {
+    config: Config,
+    master: bool,
+    transmit: bool,
+    div: u8,
+    odd: bool,
+    mck: bool,
+}
+
+/// I2S configuration
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    pub standard: Standard,
+    pub data_format: DataFormat,
+    /// Whether the clock is high when idle
+    pub clock_idle_high: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            standard: Standard::Philips,
+            data_format: DataFormat::Data16Channel16,
+            clock_idle_high: false,
+        }
+    }
+}
+
+/// I2S standard, i.e. the frame format
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Standard {
+    /// Philips I2S
+    Philips,
+    /// MSB justified (left justified)
+    Msb,
+    /// LSB justified (right justified)
+    Lsb,
+    /// PCM with a WS pulse of one clock cycle
+    PcmShortSync,
+    /// PCM with a WS pulse of 13 clock cycles
+    PcmLongSync,
+}
+
+/// Data and channel length
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DataFormat {
+    /// 16-bit data in a 16-bit channel
+    Data16Channel16,
+    /// 16-bit data in a 32-bit channel
+    Data16Channel32,
+    /// 24-bit data in a 32-bit channel
+    Data24Channel32,
+    /// 32-bit data in a 32-bit channel
+    Data32Channel32,
+}
+
+impl DataFormat {
+    fn channel_32(self) -> bool {
+        self != DataFormat::Data16Channel16
+    }
+}
+
+/// An audio channel
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Channel {
+    Left,
+    Right,
+}
+
+/// Implemented for all tuples that contain a full set of valid I2S pins
+pub trait Pins<I> {
+    /// Whether the pins include an MCK pin
+    const MCK: bool = false;
+}
+
+impl<I, CK, WS, SD> Pins<I> for (CK, WS, SD)
+where
+    CK: Ck<I>,
+    WS: Ws<I>,
+    SD: Sd<I>,
+{
+}
+
+impl<I, CK, WS, SD, MCK> Pins<I> for (CK, WS, SD, MCK)
+where
+    CK: Ck<I>,
+    WS: Ws<I>,
+    SD: Sd<I>,
+    MCK: Mck<I>,
+{
+    const MCK: bool = true;
+}
+
+/// Implemented for all pins that can function as the CK pin
+///
+/// Users of this crate should not implement this trait.
+pub trait Ck<I> {}
+
+/// Implemented for all pins that can function as the WS pin
+///
+/// Users of this crate should not implement this trait.
+pub trait Ws<I> {}
+
+/// Implemented for all pins that can function as the SD pin
+///
+/// Users of this crate should not implement this trait.
+pub trait Sd<I> {}
+
+/// Implemented for all pins that can function as the MCK pin
+///
+/// Users of this crate should not implement this trait.
+pub trait Mck<I> {}
+
+macro_rules! impl_instance {
+    (
+        $(
+            $name:ty {
+                regs: ($bus:ident, $reset:ident, $enable:ident),
+                pins: {
+                    CK: [$($ck:ty,)*],
+                    WS: [$($ws:ty,)*],
+                    SD: [$($sd:ty,)*],
+                    MCK: [$($mck:ty,)*],
+                }
+            }
+        )*
+    ) => {
+        $(
+            impl Instance for $name {
+                fn enable_clock(&self, rcc: &mut Rcc) {
+                    rcc.$bus.rstr().modify(|_, w| w.$reset().clear_bit());
+                    rcc.$bus.enr().modify(|_, w| w.$enable().enabled());
+                }
+
+                fn configure(&self, settings: Settings) {
+                    let config = settings.config;
+
+                    // The peripheral must be disabled while it's being
+                    // configured
+                    self.i2scfgr.write(|w| w.i2se().disabled());
+
+                    self.i2spr.write(|w| {
+                        // Prescaler
+                        //
+                        // This is safe, as `calculate_divider` makes sure the
+                        // value is within range.
+                        let w = unsafe { w.i2sdiv().bits(settings.div) };
+
+                        w
+                            .odd().bit(settings.odd)
+                            // Master clock output
+                            .mckoe().bit(settings.mck)
+                    });
+
+                    self.cr2.write(|w|
+                        w
+                            // Enable DMA support for the direction in use
+                            .txdmaen().bit(settings.transmit)
+                            .rxdmaen().bit(!settings.transmit)
+                    );
+
+                    self.i2scfgr.write(|w| {
+                        // Select I2S mode
+                        let w = w.i2smod().i2smode();
+
+                        // Select master or slave, transmit or receive
+                        let w = match (settings.master, settings.transmit) {
+                            (false, true) => w.i2scfg().slave_tx(),
+                            (false, false) => w.i2scfg().slave_rx(),
+                            (true, true) => w.i2scfg().master_tx(),
+                            (true, false) => w.i2scfg().master_rx(),
+                        };
+
+                        // Frame format
+                        let w = match config.standard {
+                            Standard::Philips => w.i2sstd().philips(),
+                            Standard::Msb => w.i2sstd().msb(),
+                            Standard::Lsb => w.i2sstd().lsb(),
+                            Standard::PcmShortSync => {
+                                w.i2sstd().pcm().pcmsync().short()
+                            }
+                            Standard::PcmLongSync => {
+                                w.i2sstd().pcm().pcmsync().long()
+                            }
+                        };
+
+                        // Data and channel length
+                        let w = match config.data_format {
+                            DataFormat::Data16Channel16 => {
+                                w.datlen().sixteen_bit().chlen().sixteen_bit()
+                            }
+                            DataFormat::Data16Channel32 => {
+                                w.datlen().sixteen_bit().chlen().thirty_two_bit()
+                            }
+                            DataFormat::Data24Channel32 => {
+                                w.datlen().twenty_four_bit().chlen().thirty_two_bit()
+                            }
+                            DataFormat::Data32Channel32 => {
+                                w.datlen().thirty_two_bit().chlen().thirty_two_bit()
+                            }
+                        };
+
+                        // Select clock polarity
+                        w.ckpol().bit(config.clock_idle_high)
+                    });
+
+                    // Enable I2S. In master mode, the clock starts running
+                    // right away.
+                    self.i2scfgr.modify(|_, w| w.i2se().enabled());
+                }
+
+                fn check_errors(&self) -> Result<(), Error> {
+                    let sr = self.sr.read();
+
+                    if sr.fre().is_error() {
+                        return Err(Error::FrameFormat);
+                    }
+                    if sr.ovr().is_overrun() {
+                        return Err(Error::Overrun);
+                    }
+                    if sr.udr().is_underrun() {
+                        return Err(Error::Underrun);
+                    }
+
+                    Ok(())
+                }
+
+                fn read(&self) -> nb::Result<u16, Error> {
+                    self.check_errors()?;
+
+                    if self.sr.read().rxne().is_not_empty() {
+                        return Ok(self.dr.read().dr().bits());
+                    }
+
+                    Err(nb::Error::WouldBlock)
+                }
+
+                fn send(&self, half_word: u16) -> nb::Result<(), Error> {
+                    self.check_errors()?;
+
+                    if self.sr.read().txe().is_empty() {
+                        self.dr.write(|w| w.dr().bits(half_word));
+                        return Ok(());
+                    }
+
+                    Err(nb::Error::WouldBlock)
+                }
+
+                fn channel(&self) -> Channel {
+                    if self.sr.read().chside().is_right() {
+                        Channel::Right
+                    } else {
+                        Channel::Left
+                    }
+                }
+
+                fn dr_address(&self) -> u32 {
+                    &self.dr as *const _ as _
+                }
+
+                fn disable(&self, transmit: bool) {
+                    // Wait until the last half-word has been sent. See the
+                    // procedure for disabling the I2S in the reference
+                    // manual.
+                    if transmit {
+                        while self.sr.read().txe().is_not_empty() {}
+                        while self.sr.read().bsy().is_busy() {}
+                    }
+
+                    self.i2scfgr.modify(|_, w| w.i2se().disabled());
+                }
+            }
+
+            $(
+                impl Ck<$name> for $ck {}
+            )*
+
+            $(
+                impl Ws<$name> for $ws {}
+            )*
+
+            $(
+                impl Sd<$name> for $sd {}
+            )*
+
+            $(
+                impl Mck<$name> for $mck {}
+            )*
+        )*
+    }
+}
+
+impl_instance!(
+    pac::SPI1 {
+        regs: (apb2, spi1rst, spi1en),
+        pins: {
+            CK: [
+                gpio::gpioa::PA5<Alternate<AF5>>,
+                gpio::gpiob::PB3<Alternate<AF5>>,
+                gpio::gpiog::PG11<Alternate<AF5>>,
+            ],
+            WS: [
+                gpio::gpioa::PA4<Alternate<AF5>>,
+                gpio::gpioa::PA15<Alternate<AF5>>,
+                gpio::gpiog::PG10<Alternate<AF5>>,
+            ],
+            SD: [
+                gpio::gpioa::PA7<Alternate<AF5>>,
+                gpio::gpiob::PB5<Alternate<AF5>>,
+                gpio::gpiod::PD7<Alternate<AF5>>,
+            ],
+            MCK: [
+                gpio::gpioc::PC4<Alternate<AF5>>,
+            ],
+        }
+    }
+    pac::SPI2 {
+        regs: (apb1, spi2rst, spi2en),
+        pins: {
+            CK: [
+                gpio::gpioa::PA9<Alternate<AF5>>,
+                gpio::gpiob::PB10<Alternate<AF5>>,
+                gpio::gpiob::PB13<Alternate<AF5>>,
+                gpio::gpiod::PD3<Alternate<AF5>>,
+                gpio::gpioi::PI1<Alternate<AF5>>,
+            ],
+            WS: [
+                gpio::gpiob::PB9<Alternate<AF5>>,
+                gpio::gpiob::PB12<Alternate<AF5>>,
+                gpio::gpioi::PI0<Alternate<AF5>>,
+            ],
+            SD: [
+                gpio::gpiob::PB15<Alternate<AF5>>,
+                gpio::gpioc::PC1<Alternate<AF5>>,
+                gpio::gpioc::PC3<Alternate<AF5>>,
+                gpio::gpioi::PI3<Alternate<AF5>>,
+            ],
+            MCK: [
+                gpio::gpioc::PC6<Alternate<AF5>>,
+            ],
+        }
+    }
+    pac::SPI3 {
+        regs: (apb1, spi3rst, spi3en),
+        pins: {
+            CK: [
+                gpio::gpiob::PB3<Alternate<AF6>>,
+                gpio::gpioc::PC10<Alternate<AF6>>,
+            ],
+            WS: [
+                gpio::gpioa::PA4<Alternate<AF6>>,
+                gpio::gpioa::PA15<Alternate<AF6>>,
+            ],
+            SD: [
+                gpio::gpiob::PB2<Alternate<AF7>>,
+                gpio::gpiob::PB5<Alternate<AF6>>,
+                gpio::gpioc::PC12<Alternate<AF6>>,
+                gpio::gpiod::PD6<Alternate<AF5>>,
+            ],
+            MCK: [
+                gpio::gpioc::PC7<Alternate<AF6>>,
+            ],
+        }
+    }
+);
+
+#[derive(Clone, Copy, Debug)]
+pub enum Error {
+    FrameFormat,
+    Overrun,
+    Underrun,
+    /// DMA finished both halves of a circular buffer before the first one was
+    /// handled
+    Missed,
+    /// A DMA transfer failed
+    Dma(dma::Error),
+    /// PLLI2S, which clocks the I2S peripheral in master mode, isn't enabled
+    ClockDisabled,
+    /// The sample rate can't be generated from the PLLI2S output
+    SampleRate,
+}
+
+/// Indicates that the I2S peripheral is enabled
+///
+/// The `Dir` type parameter indicates whether it transmits or receives.
+pub struct Enabled<Dir>(PhantomData<Dir>);
+
+/// Indicates that the I2S peripheral transmits
+pub struct Transmit;
+
+/// Indicates that the I2S peripheral receives
+pub struct Receive;
+
+/// Implemented for the transfer directions of the I2S peripheral
+///
+/// This is an internal trait. End users neither need to implement it, nor use
+/// it directly.
+pub trait Direction: private::Sealed {
+    const TRANSMIT: bool;
+}
+
+impl private::Sealed for Transmit {}
+impl Direction for Transmit {
+    const TRANSMIT: bool = true;
+}
+
+impl private::Sealed for Receive {}
+impl Direction for Receive {
+    const TRANSMIT: bool = false;
+}
+
+mod private {
+    /// Prevents code outside of the parent module from implementing traits
+    ///
+    /// This trait is located in a module that is not accessible outside of the
+    /// parent module. This means that any trait that requires `Sealed` cannot
+    /// be implemented only in the parent module.
+    pub trait Sealed {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::calculate_divider;
+
+    #[test]
+    fn divider_with_mck() {
+        // 86 MHz from PLLI2S, for 48 kHz with MCK at 256 * Fs
+        assert_eq!(
+            calculate_divider(86_000_000, 48_000, false, true),
+            Some((3, true, 47_991))
+        );
+    }
+
+    #[test]
+    fn divider_without_mck() {
+        assert_eq!(
+            calculate_divider(86_000_000, 48_000, false, false),
+            Some((28, false, 47_991))
+        );
+        assert_eq!(
+            calculate_divider(86_000_000, 48_000, true, false),
+            Some((14, false, 47_991))
+        );
+    }
+
+    #[test]
+    fn divider_out_of_range() {
+        assert_eq!(calculate_divider(86_000_000, 1_000, false, false), None);
+        assert_eq!(calculate_divider(86_000_000, 192_000, false, true), None);
+        assert_eq!(calculate_divider(86_000_000, 0, false, false), None);
+        assert_eq!(calculate_divider(86_000_000, u32::MAX, false, true), None);
+    }
+}
@@ -83,6 +83,9 @@ pub mod fmc;
 #[cfg(feature = "device-selected")]
 pub mod gpio;
 
+#[cfg(feature = "device-selected")]
+pub mod i2s;
+
 #[cfg(all(
     feature = "usb_fs",
     any(
@@ -33,6 +33,9 @@ impl RccExt for RCC {
                 plln: 50,
                 pllp: PLLP::Div2,
                 pllq: 2,
+                use_plli2s: false,
+                plli2sn: 192,
+                plli2sr: 2,
             },
         }
     }
@@ -184,7 +187,7 @@ impl HSEClock {
     }
 }
 
-const HSI: u32 = 16_000_000; // Hz
+pub(crate) const HSI: u32 = 16_000_000; // Hz
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PLLP {
@@ -220,6 +223,9 @@ pub struct CFGR {
     plln: u16,
     pllp: PLLP,
     pllq: u8,
+    use_plli2s: bool,
+    plli2sn: u16,
+    plli2sr: u8,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
@@ -344,6 +350,23 @@ impl CFGR {
         self
     }
 
+    /// Enable the I2S PLL with the given multiplication and division factors.
+    ///
+    /// PLLI2S shares its input clock and the `pllm` divider with the main PLL, so the I2S
+    /// clock is `(HSE or HSI) / pllm * plli2sn / plli2sr`. The resulting frequency is
+    /// reported by [`Clocks::plli2sclk`].
+    ///
+    /// `freeze` panics, if the VCO input (`(HSE or HSI) / pllm`) isn't between 1 and 2 MHz,
+    /// or the VCO output (`(HSE or HSI) / pllm * plli2sn`) isn't between 100 and 432 MHz.
+    pub fn plli2s(mut self, plli2sn: u16, plli2sr: u8) -> Self {
+        assert!(plli2sn >= 50 && plli2sn <= 432);
+        assert!(plli2sr >= 2 && plli2sr <= 7);
+        self.use_plli2s = true;
+        self.plli2sn = plli2sn;
+        self.plli2sr = plli2sr;
+        self
+    }
+
     /// Output clock calculation
     fn calculate_clocks(&self) -> (Clocks, InternalRCCConfig) {
         let mut config = InternalRCCConfig::default();
@@ -375,6 +398,26 @@ impl CFGR {
                 pll48clk >= 48_000_000 - 120_000 && pll48clk <= 48_000_000 + 120_000
             };
         }
+        let plli2sclk = if self.use_plli2s {
+            // PLLI2S shares its VCO input with the main PLL
+            let vco_input = base_clk / self.pllm as u64;
+            assert!(
+                (1_000_000..=2_000_000).contains(&vco_input),
+                "PLLI2S VCO input frequency out of range: {}",
+                vco_input
+            );
+            let vco = base_clk * self.plli2sn as u64 / self.pllm as u64;
+            assert!(
+                (100_000_000..=432_000_000).contains(&vco),
+                "PLLI2S VCO output frequency out of range: {}",
+                vco
+            );
+
+            Some(Hertz((vco / self.plli2sr as u64) as u32))
+        } else {
+            None
+        };
+
         // SYSCLK, must be <= 216 Mhz. By default, HSI/HSE frequency is chosen
         assert!(sysclk <= 216_000_000);
         let sysclk = sysclk as u32;
@@ -525,6 +568,7 @@ impl CFGR {
             timclk1: Hertz(timclk1),
             timclk2: Hertz(timclk2),
             pll48clk_valid,
+            plli2sclk,
         };
 
         (clocks, config)
@@ -739,6 +783,27 @@ impl CFGR {
             while rcc.cr.read().pllrdy().is_not_ready() {}
         }
 
+        if self.use_plli2s {
+            if !(self.use_pll || self.use_pll48clk) {
+                // PLLI2S takes its input from the main PLL source and divider
+                rcc.pllcfgr.modify(|_, w| unsafe {
+                    w.pllm().bits(self.pllm);
+                    w.pllsrc().bit(self.hse.is_some())
+                });
+            }
+
+            rcc.cr.modify(|_, w| w.plli2son().off());
+            rcc.plli2scfgr.modify(|_, w| unsafe {
+                w.plli2sn().bits(self.plli2sn);
+                w.plli2sr().bits(self.plli2sr)
+            });
+            rcc.cr.modify(|_, w| w.plli2son().on());
+            while rcc.cr.read().plli2srdy().is_not_ready() {}
+
+            // Clock I2S from PLLI2S rather than the external I2S_CKIN pin
+            rcc.cfgr.modify(|_, w| w.i2ssrc().plli2s());
+        }
+
         if self.use_pll48clk {
             // set source clock for 48 MHz to main PLL
             rcc.dckcfgr2.modify(|_, w| w.ck48msel().bit(false));
@@ -790,6 +855,7 @@ pub struct Clocks {
     timclk1: Hertz,
     timclk2: Hertz,
     pll48clk_valid: bool,
+    plli2sclk: Option<Hertz>,
 }
 
 impl Clocks {
@@ -829,6 +895,11 @@ impl Clocks {
         // USB specification allow +-0.25%
         self.pll48clk_valid
     }
+
+    /// Returns the frequency of the I2S PLL output, if it was enabled
+    pub fn plli2sclk(&self) -> Option<Hertz> {
+        self.plli2sclk
+    }
 }
 
 pub trait GetBusFreq {
@@ -1108,6 +1179,9 @@ mod tests {
             plln: 50,
             pllp: PLLP::Div2,
             pllq: 2,
+            use_plli2s: false,
+            plli2sn: 192,
+            plli2sr: 2,
         };
 
         let mut cfgr = cfgr
@@ -1141,6 +1215,9 @@ mod tests {
             plln: 50,
             pllp: PLLP::Div2,
             pllq: 2,
+            use_plli2s: false,
+            plli2sn: 192,
+            plli2sr: 2,
         };
 
         let mut cfgr = cfgr
@@ -1173,6 +1250,9 @@ mod tests {
             plln: 50,
             pllp: PLLP::Div2,
             pllq: 2,
+            use_plli2s: false,
+            plli2sn: 192,
+            plli2sr: 2,
         };
 
         let mut cfgr = cfgr
@@ -1204,6 +1284,9 @@ mod tests {
             plln: 50,
             pllp: PLLP::Div2,
             pllq: 2,
+            use_plli2s: false,
+            plli2sn: 192,
+            plli2sr: 2,
         };
 
         cfgr.pll_configure();
@@ -1211,4 +1294,69 @@ mod tests {
         let (clocks, _config) = cfgr.calculate_clocks();
         assert_eq!(clocks.sysclk().0, 16_000_000);
     }
+
+    #[test]
+    fn test_plli2s() {
+        use super::{HSEClock, HSEClockMode, PLLP};
+        use crate::time::U32Ext;
+
+        let cfgr = CFGR {
+            hse: None,
+            hclk: None,
+            sysclk: None,
+            pclk1: None,
+            pclk2: None,
+            use_pll: false,
+            use_pll48clk: false,
+            pllm: 2,
+            plln: 50,
+            pllp: PLLP::Div2,
+            pllq: 2,
+            use_plli2s: false,
+            plli2sn: 192,
+            plli2sr: 2,
+        };
+
+        let mut cfgr = cfgr
+            .hse(HSEClock::new(25.mhz(), HSEClockMode::Bypass))
+            .pllm(25)
+            .plli2s(258, 3);
+        cfgr.pll_configure();
+
+        let (clocks, _config) = cfgr.calculate_clocks();
+        assert_eq!(clocks.plli2sclk().unwrap().0, 86_000_000);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_plli2s_vco_too_low() {
+        use super::{HSEClock, HSEClockMode, PLLP};
+        use crate::time::U32Ext;
+
+        let cfgr = CFGR {
+            hse: None,
+            hclk: None,
+            sysclk: None,
+            pclk1: None,
+            pclk2: None,
+            use_pll: false,
+            use_pll48clk: false,
+            pllm: 2,
+            plln: 50,
+            pllp: PLLP::Div2,
+            pllq: 2,
+            use_plli2s: false,
+            plli2sn: 192,
+            plli2sr: 2,
+        };
+
+        // 1 MHz * 60 is below the minimum VCO frequency
+        let mut cfgr = cfgr
+            .hse(HSEClock::new(25.mhz(), HSEClockMode::Bypass))
+            .pllm(25)
+            .plli2s(60, 2);
+        cfgr.pll_configure();
+
+        cfgr.calculate_clocks();
+    }
 }
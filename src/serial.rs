@@ -4,34 +4,47 @@ use core::ops::Deref;
 use core::ops::DerefMut;
 use core::pin::Pin;
 use core::ptr;
+use core::sync::atomic::{AtomicBool, Ordering};
 
 use as_slice::{AsMutSlice, AsSlice};
+use heapless::{
+    spsc::{Consumer, Producer, Queue},
+    ArrayLength,
+};
 
 use crate::dma;
 use crate::hal::prelude::*;
 use crate::hal::serial;
+use crate::hal::spi::{self, Phase, Polarity};
 use crate::pac;
 use crate::state;
 use crate::time::U32Ext;
 use nb::block;
 
 #[cfg(any(feature = "device-selected",))]
-use crate::pac::{RCC, UART4, UART5, UART7, USART1, USART2, USART3, USART6};
+use crate::pac::{RCC, UART4, UART5, UART7, UART8, USART1, USART2, USART3, USART6};
 
 #[cfg(any(feature = "device-selected",))]
 use crate::gpio::{
-    gpioa::{PA0, PA1, PA10, PA2, PA3, PA9},
-    gpiob::{PB10, PB11, PB6, PB7},
-    gpioc::{PC10, PC11, PC12, PC6, PC7},
-    gpiod::{PD2, PD5, PD6, PD8, PD9},
-    gpioe::{PE7, PE8},
-    gpiof::{PF6, PF7},
-    gpiog::{PG14, PG9},
+    gpioa::{PA0, PA1, PA10, PA11, PA12, PA15, PA2, PA3, PA4, PA8, PA9},
+    gpiob::{PB0, PB10, PB11, PB12, PB13, PB14, PB6, PB7},
+    gpioc::{PC10, PC11, PC12, PC6, PC7, PC8, PC9},
+    gpiod::{PD10, PD11, PD12, PD14, PD15, PD2, PD3, PD4, PD5, PD6, PD7, PD8, PD9},
+    gpioe::{PE0, PE1, PE10, PE7, PE8, PE9},
+    gpiof::{PF6, PF7, PF8, PF9},
+    gpiog::{PG12, PG13, PG14, PG15, PG7, PG8, PG9},
     Alternate, AF7, AF8,
 };
 
-use crate::rcc::Clocks;
-use crate::time::Bps;
+use crate::rcc::{Clocks, HSI};
+use crate::time::{Bps, Hertz};
+
+/// LSE frequency, in Hz
+const LSE: u32 = 32_768;
+
+/// Maximum deviation of the generated baud rate from the requested one, in
+/// per mille
+pub const BAUD_RATE_TOLERANCE: u32 = 20;
 
 /// Serial error
 #[derive(Debug)]
@@ -44,6 +57,12 @@ pub enum Error {
     Overrun,
     /// Parity check error
     Parity,
+    /// The baud rate couldn't be detected
+    AutoBaudRate,
+    /// The baud rate can't be generated from the kernel clock
+    BaudRate,
+    /// The configuration contains an unsupported combination or value
+    InvalidConfig,
     #[doc(hidden)]
     _Extensible,
 }
@@ -51,6 +70,10 @@ pub enum Error {
 pub trait Pins<USART> {}
 pub trait PinTx<USART> {}
 pub trait PinRx<USART> {}
+pub trait PinRts<USART> {}
+pub trait PinCts<USART> {}
+pub trait PinDe<USART> {}
+pub trait PinCk<USART> {}
 
 impl<USART, TX, RX> Pins<USART> for (TX, RX)
 where
@@ -59,42 +82,255 @@ where
 {
 }
 
-#[cfg(any(feature = "device-selected",))]
-impl PinTx<USART1> for PA9<Alternate<AF7>> {}
-impl PinTx<USART1> for PB6<Alternate<AF7>> {}
-impl PinTx<USART2> for PA2<Alternate<AF7>> {}
-impl PinTx<USART2> for PD5<Alternate<AF7>> {}
-impl PinTx<USART3> for PB10<Alternate<AF7>> {}
-impl PinTx<USART3> for PC10<Alternate<AF7>> {}
-impl PinTx<USART3> for PD8<Alternate<AF7>> {}
-impl PinTx<UART4> for PA0<Alternate<AF8>> {}
-impl PinTx<UART4> for PC10<Alternate<AF8>> {}
-impl PinTx<UART5> for PC12<Alternate<AF8>> {}
-impl PinTx<USART6> for PC6<Alternate<AF8>> {}
-impl PinTx<USART6> for PG14<Alternate<AF8>> {}
-impl PinTx<UART7> for PE8<Alternate<AF8>> {}
-impl PinTx<UART7> for PF7<Alternate<AF8>> {}
+/// Pins for hardware flow control, see [`Config::flow_control`]
+impl<USART, TX, RX, RTS, CTS> Pins<USART> for (TX, RX, RTS, CTS)
+where
+    TX: PinTx<USART>,
+    RX: PinRx<USART>,
+    RTS: PinRts<USART>,
+    CTS: PinCts<USART>,
+{
+}
+
+/// Pins for RS-485 driver enable, see [`Config::driver_enable`]
+impl<USART, TX, RX, DE> Pins<USART> for (TX, RX, DE)
+where
+    TX: PinTx<USART>,
+    RX: PinRx<USART>,
+    DE: PinDe<USART>,
+{
+}
+
+/// Placeholder for a pin when no RTS pin is required
+pub struct NoRts;
+impl<USART> PinRts<USART> for NoRts {}
+
+/// Placeholder for a pin when no CTS pin is required
+pub struct NoCts;
+impl<USART> PinCts<USART> for NoCts {}
+
+/// Placeholder for a pin when no RX pin is required, e.g. in half-duplex mode
+pub struct NoRx;
+impl<USART> PinRx<USART> for NoRx {}
+
+macro_rules! impl_pins {
+    ($($Pin:ident<$USART:ident>: [$($PX:ty),+ $(,)?],)+) => {
+        $(
+            $(
+                impl $Pin<$USART> for $PX {}
+            )+
+        )+
+    }
+}
 
 #[cfg(any(feature = "device-selected",))]
-impl PinRx<USART1> for PA10<Alternate<AF7>> {}
-impl PinRx<USART1> for PB7<Alternate<AF7>> {}
-impl PinRx<USART2> for PA3<Alternate<AF7>> {}
-impl PinRx<USART2> for PD6<Alternate<AF7>> {}
-impl PinRx<USART3> for PB11<Alternate<AF7>> {}
-impl PinRx<USART3> for PC11<Alternate<AF7>> {}
-impl PinRx<USART3> for PD9<Alternate<AF7>> {}
-impl PinRx<UART4> for PA1<Alternate<AF8>> {}
-impl PinRx<UART4> for PC11<Alternate<AF8>> {}
-impl PinRx<UART5> for PD2<Alternate<AF8>> {}
-impl PinRx<USART6> for PC7<Alternate<AF8>> {}
-impl PinRx<USART6> for PG9<Alternate<AF8>> {}
-impl PinRx<UART7> for PE7<Alternate<AF8>> {}
-impl PinRx<UART7> for PF6<Alternate<AF8>> {}
+impl_pins! {
+    PinTx<USART1>: [
+        PA9<Alternate<AF7>>,
+        PB6<Alternate<AF7>>,
+    ],
+    PinTx<USART2>: [
+        PA2<Alternate<AF7>>,
+        PD5<Alternate<AF7>>,
+    ],
+    PinTx<USART3>: [
+        PB10<Alternate<AF7>>,
+        PC10<Alternate<AF7>>,
+        PD8<Alternate<AF7>>,
+    ],
+    PinTx<UART4>: [
+        PA0<Alternate<AF8>>,
+        PC10<Alternate<AF8>>,
+    ],
+    PinTx<UART5>: [PC12<Alternate<AF8>>],
+    PinTx<USART6>: [
+        PC6<Alternate<AF8>>,
+        PG14<Alternate<AF8>>,
+    ],
+    PinTx<UART7>: [
+        PE8<Alternate<AF8>>,
+        PF7<Alternate<AF8>>,
+    ],
+    PinTx<UART8>: [PE1<Alternate<AF8>>],
+
+    PinRx<USART1>: [
+        PA10<Alternate<AF7>>,
+        PB7<Alternate<AF7>>,
+    ],
+    PinRx<USART2>: [
+        PA3<Alternate<AF7>>,
+        PD6<Alternate<AF7>>,
+    ],
+    PinRx<USART3>: [
+        PB11<Alternate<AF7>>,
+        PC11<Alternate<AF7>>,
+        PD9<Alternate<AF7>>,
+    ],
+    PinRx<UART4>: [
+        PA1<Alternate<AF8>>,
+        PC11<Alternate<AF8>>,
+    ],
+    PinRx<UART5>: [PD2<Alternate<AF8>>],
+    PinRx<USART6>: [
+        PC7<Alternate<AF8>>,
+        PG9<Alternate<AF8>>,
+    ],
+    PinRx<UART7>: [
+        PE7<Alternate<AF8>>,
+        PF6<Alternate<AF8>>,
+    ],
+    PinRx<UART8>: [PE0<Alternate<AF8>>],
+
+    PinRts<USART1>: [PA12<Alternate<AF7>>],
+    PinRts<USART2>: [
+        PA1<Alternate<AF7>>,
+        PD4<Alternate<AF7>>,
+    ],
+    PinRts<USART3>: [
+        PB14<Alternate<AF7>>,
+        PD12<Alternate<AF7>>,
+    ],
+    PinRts<UART4>: [PA15<Alternate<AF8>>],
+    PinRts<UART5>: [PC8<Alternate<AF7>>],
+    PinRts<USART6>: [
+        PG8<Alternate<AF8>>,
+        PG12<Alternate<AF8>>,
+    ],
+    PinRts<UART7>: [
+        PE9<Alternate<AF8>>,
+        PF8<Alternate<AF8>>,
+    ],
+    PinRts<UART8>: [PD15<Alternate<AF8>>],
+
+    PinCts<USART1>: [PA11<Alternate<AF7>>],
+    PinCts<USART2>: [
+        PA0<Alternate<AF7>>,
+        PD3<Alternate<AF7>>,
+    ],
+    PinCts<USART3>: [
+        PB13<Alternate<AF7>>,
+        PD11<Alternate<AF7>>,
+    ],
+    PinCts<UART4>: [PB0<Alternate<AF8>>],
+    PinCts<UART5>: [PC9<Alternate<AF7>>],
+    PinCts<USART6>: [
+        PG13<Alternate<AF8>>,
+        PG15<Alternate<AF8>>,
+    ],
+    PinCts<UART7>: [
+        PE10<Alternate<AF8>>,
+        PF9<Alternate<AF8>>,
+    ],
+    PinCts<UART8>: [PD14<Alternate<AF8>>],
+
+    PinDe<USART1>: [PA12<Alternate<AF7>>],
+    PinDe<USART2>: [
+        PA1<Alternate<AF7>>,
+        PD4<Alternate<AF7>>,
+    ],
+    PinDe<USART3>: [
+        PB14<Alternate<AF7>>,
+        PD12<Alternate<AF7>>,
+    ],
+    PinDe<UART4>: [PA15<Alternate<AF8>>],
+    PinDe<UART5>: [PC8<Alternate<AF7>>],
+    PinDe<USART6>: [
+        PG8<Alternate<AF8>>,
+        PG12<Alternate<AF8>>,
+    ],
+    PinDe<UART7>: [
+        PE9<Alternate<AF8>>,
+        PF8<Alternate<AF8>>,
+    ],
+    PinDe<UART8>: [PD15<Alternate<AF8>>],
+
+    // Only the USARTs have a clock output, the UARTs don't
+    PinCk<USART1>: [PA8<Alternate<AF7>>],
+    PinCk<USART2>: [
+        PA4<Alternate<AF7>>,
+        PD7<Alternate<AF7>>,
+    ],
+    PinCk<USART3>: [
+        PB12<Alternate<AF7>>,
+        PC12<Alternate<AF7>>,
+        PD10<Alternate<AF7>>,
+    ],
+    PinCk<USART6>: [
+        PC8<Alternate<AF8>>,
+        PG7<Alternate<AF8>>,
+    ],
+}
+
+// Alternate pins of the newer devices
+#[cfg(any(
+    feature = "stm32f722",
+    feature = "stm32f723",
+    feature = "stm32f730",
+    feature = "stm32f732",
+    feature = "stm32f733",
+    feature = "stm32f765",
+    feature = "stm32f767",
+    feature = "stm32f769",
+    feature = "stm32f777",
+    feature = "stm32f778",
+    feature = "stm32f779",
+))]
+impl_pins! {
+    PinTx<UART4>: [
+        crate::gpio::gpiod::PD1<Alternate<AF8>>,
+        crate::gpio::gpioh::PH13<Alternate<AF8>>,
+    ],
+    PinTx<UART5>: [
+        crate::gpio::gpiob::PB9<Alternate<AF7>>,
+        crate::gpio::gpiob::PB13<Alternate<AF8>>,
+    ],
+    PinTx<UART7>: [
+        crate::gpio::gpioa::PA15<Alternate<crate::gpio::AF12>>,
+        crate::gpio::gpiob::PB4<Alternate<crate::gpio::AF12>>,
+    ],
+
+    PinRx<UART4>: [
+        crate::gpio::gpiod::PD0<Alternate<AF8>>,
+        crate::gpio::gpioi::PI9<Alternate<AF8>>,
+    ],
+    PinRx<UART5>: [
+        crate::gpio::gpiob::PB8<Alternate<AF7>>,
+        crate::gpio::gpiob::PB12<Alternate<AF8>>,
+    ],
+    PinRx<UART7>: [
+        crate::gpio::gpioa::PA8<Alternate<crate::gpio::AF12>>,
+        crate::gpio::gpiob::PB3<Alternate<crate::gpio::AF12>>,
+    ],
+}
+
+#[cfg(any(
+    feature = "stm32f765",
+    feature = "stm32f767",
+    feature = "stm32f769",
+    feature = "stm32f777",
+    feature = "stm32f778",
+    feature = "stm32f779",
+))]
+impl_pins! {
+    PinTx<USART1>: [crate::gpio::gpiob::PB14<Alternate<crate::gpio::AF4>>],
+    PinTx<UART4>: [crate::gpio::gpioa::PA12<Alternate<crate::gpio::AF6>>],
+    PinTx<UART5>: [crate::gpio::gpiob::PB6<Alternate<crate::gpio::AF1>>],
+
+    PinRx<USART1>: [crate::gpio::gpiob::PB15<Alternate<crate::gpio::AF4>>],
+    PinRx<UART4>: [crate::gpio::gpioa::PA11<Alternate<crate::gpio::AF6>>],
+    PinRx<UART5>: [crate::gpio::gpiob::PB5<Alternate<crate::gpio::AF1>>],
+
+    PinRts<UART4>: [crate::gpio::gpiob::PB15<Alternate<AF8>>],
+    PinCts<UART4>: [crate::gpio::gpiob::PB14<Alternate<AF8>>],
+    PinDe<UART4>: [crate::gpio::gpiob::PB15<Alternate<AF8>>],
+}
 
 /// Serial abstraction
 pub struct Serial<USART, PINS> {
     usart: USART,
     pins: PINS,
+    /// Kernel clock frequency
+    clock: Hertz,
 }
 
 impl<USART, PINS> Serial<USART, PINS>
@@ -102,117 +338,1081 @@ where
     PINS: Pins<USART>,
     USART: Instance,
 {
-    pub fn new(usart: USART, pins: PINS, clocks: Clocks, config: Config) -> Self {
+    /// Configures the USART
+    ///
+    /// Returns [`Error::BaudRate`] if the requested baud rate can't be
+    /// generated from the selected kernel clock within
+    /// [`BAUD_RATE_TOLERANCE`], and [`Error::InvalidConfig`] if `config`
+    /// combines 9 data bits with parity, or the receiver timeout or a driver
+    /// enable time is out of range.
+    pub fn new(usart: USART, pins: PINS, clocks: Clocks, config: Config) -> Result<Self, Error> {
         // NOTE(unsafe) This executes only during initialisation
         let rcc = unsafe { &(*RCC::ptr()) };
 
         // TODO: The unsafe calls below should be replaced with accessing
         //       the correct registers directly.
 
-        USART::select_sysclock(rcc);
-        USART::enable_clock(rcc);
-
-        // Calculate correct baudrate divisor on the fly
-        let brr = match config.oversampling {
-            Oversampling::By8 => {
-                usart.cr1.modify(|_, w| w.over8().set_bit());
+        let kernel_clock = match config.clock_source {
+            ClockSource::Pclk => USART::pclk(&clocks).0,
+            ClockSource::Sysclk => clocks.sysclk().0,
+            ClockSource::Hsi => HSI,
+            ClockSource::Lse => LSE,
+        };
+        let brr = calculate_brr(kernel_clock, config.baud_rate.0, config.oversampling)?;
 
-                let usart_div = 2 * clocks.sysclk().0 / config.baud_rate.0;
+        // The M bits select the word length including the parity bit
+        let word_length = match (config.word_length, config.parity) {
+            (WordLength::DataBits7, Parity::ParityNone) => 7,
+            (WordLength::DataBits7, _) | (WordLength::DataBits8, Parity::ParityNone) => 8,
+            (WordLength::DataBits8, _) | (WordLength::DataBits9, Parity::ParityNone) => 9,
+            (WordLength::DataBits9, _) => return Err(Error::InvalidConfig),
+        };
 
-                0xfff0 & usart_div | 0x0008 & 0 | 0x0007 & ((usart_div & 0x000f) >> 1)
+        // RTO is 24 bits wide
+        if let Some(timeout) = config.receiver_timeout {
+            if timeout >= 1 << 24 {
+                return Err(Error::InvalidConfig);
             }
-            Oversampling::By16 => {
-                usart.cr1.modify(|_, w| w.over8().clear_bit());
+        }
 
-                clocks.sysclk().0 / config.baud_rate.0
+        // DEAT and DEDT are 5 bits wide
+        if let Some(de) = &config.driver_enable {
+            if de.assertion_time >= 32 || de.deassertion_time >= 32 {
+                return Err(Error::InvalidConfig);
             }
-        };
+        }
+
+        USART::select_clock(rcc, config.clock_source);
+        USART::enable_clock(rcc);
 
+        usart
+            .cr1
+            .modify(|_, w| w.over8().bit(config.oversampling == Oversampling::By8));
         usart.brr.write(|w| unsafe { w.bits(brr) });
 
-        // Set character match and reset other registers to disable advanced USART features
+        // Set the frame format
+        usart.cr1.modify(|_, w| {
+            w.m1()
+                .bit(word_length == 7)
+                .m0()
+                .bit(word_length == 9)
+                .pce()
+                .bit(config.parity != Parity::ParityNone)
+                .ps()
+                .bit(config.parity == Parity::ParityOdd)
+        });
+
+        // Set the receiver timeout
+        if let Some(timeout) = config.receiver_timeout {
+            usart.rtor.write(|w| w.rto().bits(timeout));
+        }
+
+        // Set character match, stop bits, receiver timeout and reset other
+        // registers to disable advanced USART features
         let ch = config.character_match.unwrap_or(0);
-        usart.cr2.write(|w| w.add().bits(ch));
+        usart.cr2.write(|w| {
+            let w = w
+                .add()
+                .bits(ch)
+                .rtoen()
+                .bit(config.receiver_timeout.is_some());
+            match config.stop_bits {
+                StopBits::STOP0P5 => w.stop().stop0p5(),
+                StopBits::STOP1 => w.stop().stop1(),
+                StopBits::STOP1P5 => w.stop().stop1p5(),
+                StopBits::STOP2 => w.stop().stop2(),
+            }
+        });
+
+        // Set the driver enable timing. DEAT and DEDT can only be written
+        // while the USART is disabled.
+        if let Some(de) = &config.driver_enable {
+            usart.cr1.modify(|_, w| {
+                w.deat()
+                    .bits(de.assertion_time)
+                    .dedt()
+                    .bits(de.deassertion_time)
+            });
+        }
+
+        // Enable DMA, hardware flow control and driver enable
+        let (rtse, ctse) = match config.flow_control {
+            FlowControl::None => (false, false),
+            FlowControl::Rts => (true, false),
+            FlowControl::Cts => (false, true),
+            FlowControl::RtsCts => (true, true),
+        };
+        usart.cr3.write(|w| {
+            w.dmat()
+                .enabled()
+                .dmar()
+                .enabled()
+                .rtse()
+                .bit(rtse)
+                .ctse()
+                .bit(ctse)
+                .dem()
+                .bit(config.driver_enable.is_some())
+                .dep()
+                .bit(matches!(
+                    config.driver_enable,
+                    Some(DriverEnable {
+                        polarity: DriverEnablePolarity::ActiveLow,
+                        ..
+                    })
+                ))
+        });
 
         // Enable transmission and receiving
         usart
             .cr1
             .modify(|_, w| w.te().enabled().re().enabled().ue().enabled());
 
-        // Enable DMA
-        usart.cr3.write(|w| w.dmat().enabled().dmar().enabled());
+        Ok(Serial {
+            usart,
+            pins,
+            clock: Hertz(kernel_clock),
+        })
+    }
+
+    /// Starts listening for an interrupt event
+    pub fn listen(&mut self, event: Event) {
+        match event {
+            Event::Rxne => self.usart.cr1.modify(|_, w| w.rxneie().set_bit()),
+            Event::Txe => self.usart.cr1.modify(|_, w| w.txeie().set_bit()),
+            Event::CharacterMatch => self.usart.cr1.modify(|_, w| w.cmie().set_bit()),
+            Event::Idle => self.usart.cr1.modify(|_, w| w.idleie().set_bit()),
+            Event::ReceiverTimeout => self.usart.cr1.modify(|_, w| w.rtoie().set_bit()),
+            Event::LinBreak => self.usart.cr2.modify(|_, w| w.lbdie().set_bit()),
+            Event::Error => {
+                self.usart.cr3.modify(|_, w| w.eie().set_bit());
+                self.usart.cr1.modify(|_, w| w.peie().set_bit());
+            }
+        }
+    }
+
+    /// End listening for an interrupt event
+    pub fn unlisten(&mut self, event: Event) {
+        match event {
+            Event::Rxne => self.usart.cr1.modify(|_, w| w.rxneie().clear_bit()),
+            Event::Txe => self.usart.cr1.modify(|_, w| w.txeie().clear_bit()),
+            Event::CharacterMatch => self.usart.cr1.modify(|_, w| w.cmie().clear_bit()),
+            Event::Idle => self.usart.cr1.modify(|_, w| w.idleie().clear_bit()),
+            Event::ReceiverTimeout => self.usart.cr1.modify(|_, w| w.rtoie().clear_bit()),
+            Event::LinBreak => self.usart.cr2.modify(|_, w| w.lbdie().clear_bit()),
+            Event::Error => {
+                self.usart.cr3.modify(|_, w| w.eie().clear_bit());
+                self.usart.cr1.modify(|_, w| w.peie().clear_bit());
+            }
+        }
+    }
+
+    pub fn split(self) -> (Tx<USART>, Rx<USART>) {
+        (
+            Tx {
+                _usart: PhantomData,
+            },
+            Rx {
+                _usart: PhantomData,
+            },
+        )
+    }
+
+    pub fn release(self) -> (USART, PINS) {
+        (self.usart, self.pins)
+    }
+
+    /// Switches the USART to LIN mode
+    ///
+    /// The frame format is set to 8 data bits without parity and 1 stop bit,
+    /// as required by LIN.
+    pub fn into_lin(mut self, break_length: LinBreakLength) -> Lin<USART, PINS> {
+        self.reconfigure(|usart| {
+            usart
+                .cr1
+                .modify(|_, w| w.m1().clear_bit().m0().clear_bit().pce().clear_bit());
+            usart.cr2.modify(|_, w| {
+                w.stop()
+                    .stop1()
+                    .clken()
+                    .clear_bit()
+                    .lbdl()
+                    .bit(break_length == LinBreakLength::Bits11)
+                    .linen()
+                    .set_bit()
+            });
+            usart
+                .cr3
+                .modify(|_, w| w.scen().clear_bit().hdsel().clear_bit().iren().clear_bit());
+        });
 
-        Serial { usart, pins }
+        Lin { serial: self }
     }
 
-    /// Starts listening for an interrupt event
-    pub fn listen(&mut self, event: Event) {
-        match event {
-            Event::Rxne => self.usart.cr1.modify(|_, w| w.rxneie().set_bit()),
-            Event::Txe => self.usart.cr1.modify(|_, w| w.txeie().set_bit()),
-            Event::CharacterMatch => self.usart.cr1.modify(|_, w| w.cmie().set_bit()),
-            Event::Error => self.usart.cr3.modify(|_, w| w.eie().set_bit()),
+    /// Switches the USART to IrDA SIR mode
+    pub fn into_irda(mut self, mode: IrdaMode) -> Irda<USART, PINS> {
+        // The prescaler must be 1 in normal mode
+        let (low_power, prescaler) = match mode {
+            IrdaMode::Normal => (false, 1),
+            IrdaMode::LowPower { prescaler } => {
+                assert!(prescaler > 0);
+                (true, prescaler)
+            }
+        };
+
+        self.reconfigure(|usart| {
+            usart.gtpr.modify(|_, w| w.psc().bits(prescaler));
+            usart
+                .cr2
+                .modify(|_, w| w.stop().stop1().clken().clear_bit().linen().clear_bit());
+            usart.cr3.modify(|_, w| {
+                w.scen()
+                    .clear_bit()
+                    .hdsel()
+                    .clear_bit()
+                    .irlp()
+                    .bit(low_power)
+                    .iren()
+                    .set_bit()
+            });
+        });
+
+        Irda { serial: self }
+    }
+
+    /// Switches the USART to smartcard mode
+    ///
+    /// The frame format is set to 8 data bits with parity and 1.5 stop bits,
+    /// as required by ISO 7816-3, keeping the configured parity (usually
+    /// even). The TX pin should be configured as open-drain, as it is used
+    /// to both send and receive data.
+    pub fn into_smartcard<CK>(
+        mut self,
+        ck: CK,
+        config: SmartcardConfig,
+    ) -> Smartcard<USART, PINS, CK>
+    where
+        CK: PinCk<USART>,
+    {
+        assert!(config.prescaler > 0 && config.prescaler < 32);
+        assert!(config.retries < 8);
+
+        self.reconfigure(|usart| {
+            usart
+                .cr1
+                .modify(|_, w| w.m1().clear_bit().m0().set_bit().pce().set_bit());
+            usart
+                .gtpr
+                .write(|w| w.gt().bits(config.guard_time).psc().bits(config.prescaler));
+            usart
+                .cr2
+                .modify(|_, w| w.stop().stop1p5().linen().clear_bit().clken().set_bit());
+            usart.cr3.modify(|_, w| {
+                w.hdsel()
+                    .clear_bit()
+                    .iren()
+                    .clear_bit()
+                    .scarcnt()
+                    .bits(config.retries)
+                    .nack()
+                    .bit(config.nack)
+                    .scen()
+                    .set_bit()
+            });
+        });
+
+        Smartcard { serial: self, ck }
+    }
+
+    /// Disables the USART once the last frame has been sent, applies `f` and
+    /// enables the USART again
+    ///
+    /// Most of the mode configuration can only be changed while the USART is
+    /// disabled.
+    fn reconfigure<F>(&mut self, f: F)
+    where
+        F: FnOnce(&USART),
+    {
+        while self.usart.isr.read().tc().bit_is_clear() {}
+
+        self.usart.cr1.modify(|_, w| w.ue().disabled());
+        f(&self.usart);
+        self.usart.cr1.modify(|_, w| w.ue().enabled());
+    }
+
+    /// Turns this into an interrupt-driven [`BufferedSerial`]
+    ///
+    /// Listens for [`Event::Rxne`] and [`Event::Error`]. Returns the thread
+    /// side, which reads from and writes to `buffers`, and the interrupt
+    /// handler side, whose [`BufferedSerialIsr::on_interrupt`] must be called
+    /// from the USART interrupt handler.
+    pub fn into_buffered<'a, TXN, RXN>(
+        mut self,
+        buffers: &'a mut SerialBuffers<TXN, RXN>,
+    ) -> BufferedSerialHalves<'a, USART, PINS, TXN, RXN>
+    where
+        TXN: ArrayLength<u8>,
+        RXN: ArrayLength<Result<u8, Error>>,
+    {
+        let SerialBuffers { tx, rx, overrun } = buffers;
+        let (tx_producer, tx_consumer) = tx.split();
+        let (rx_producer, rx_consumer) = rx.split();
+        let overrun = &*overrun;
+
+        self.listen(Event::Rxne);
+        self.listen(Event::Error);
+
+        let serial = BufferedSerial {
+            serial: self,
+            tx: tx_producer,
+            rx: rx_consumer,
+            overrun,
+        };
+        let isr = BufferedSerialIsr {
+            tx: tx_consumer,
+            rx: rx_producer,
+            overrun,
+            _usart: PhantomData,
+        };
+        (serial, isr)
+    }
+
+    /// Sends a break character
+    ///
+    /// The break is sent after the frame that is currently being transmitted.
+    pub fn send_break(&mut self) {
+        self.usart.rqr.write(|w| w.sbkrq().set_bit());
+    }
+
+    /// Starts automatic baud rate detection
+    ///
+    /// The baud rate is measured on the next character received, which must
+    /// match `mode`. Use [`auto_baud_rate`](Serial::auto_baud_rate) to wait
+    /// for the result. Calling this again restarts the detection.
+    pub fn start_auto_baud(&mut self, mode: AutoBaudMode) {
+        let abrmode = match mode {
+            AutoBaudMode::StartBit => 0,
+            AutoBaudMode::FallingEdge => 1,
+            AutoBaudMode::Frame0x7F => 2,
+            AutoBaudMode::Frame0x55 => 3,
+        };
+
+        self.reconfigure(|usart| {
+            usart
+                .cr2
+                .modify(|_, w| w.abrmod().bits(abrmode).abren().set_bit())
+        });
+
+        // Clear the result of a previous detection
+        self.usart.rqr.write(|w| w.abrrq().set_bit());
+    }
+
+    /// Returns the baud rate detected by automatic baud rate detection
+    ///
+    /// Returns [`Error::AutoBaudRate`] if the detection failed, in which case
+    /// it has to be restarted using
+    /// [`start_auto_baud`](Serial::start_auto_baud). The BRR register is
+    /// updated by the hardware, so data can be received at the detected baud
+    /// rate right away.
+    pub fn auto_baud_rate(&mut self) -> nb::Result<Bps, Error> {
+        let isr = self.usart.isr.read();
+
+        if isr.abre().bit_is_set() {
+            return Err(nb::Error::Other(Error::AutoBaudRate));
+        }
+        if isr.abrf().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let brr = self.usart.brr.read().bits();
+        let (clock, usart_div) = if self.usart.cr1.read().over8().bit_is_set() {
+            // BRR[2:0] is USARTDIV[3:0] shifted right by one
+            (2 * self.clock.0, brr & 0xfff0 | (brr & 0x0007) << 1)
+        } else {
+            (self.clock.0, brr)
+        };
+        if usart_div == 0 {
+            return Err(nb::Error::Other(Error::AutoBaudRate));
+        }
+
+        Ok(Bps(clock / usart_div))
+    }
+
+    /// Stops automatic baud rate detection
+    ///
+    /// The detected baud rate is kept.
+    pub fn stop_auto_baud(&mut self) {
+        self.reconfigure(|usart| usart.cr2.modify(|_, w| w.abren().clear_bit()));
+    }
+}
+
+impl<USART, PINS> serial::Read<u8> for Serial<USART, PINS>
+where
+    USART: Instance,
+{
+    type Error = Error;
+
+    fn read(&mut self) -> nb::Result<u8, Error> {
+        let mut rx: Rx<USART> = Rx {
+            _usart: PhantomData,
+        };
+        rx.read()
+    }
+}
+
+impl<USART, PINS> serial::Read<u16> for Serial<USART, PINS>
+where
+    USART: Instance,
+{
+    type Error = Error;
+
+    fn read(&mut self) -> nb::Result<u16, Error> {
+        let mut rx: Rx<USART> = Rx {
+            _usart: PhantomData,
+        };
+        rx.read()
+    }
+}
+
+impl<USART, PINS> serial::Write<u8> for Serial<USART, PINS>
+where
+    USART: Instance,
+{
+    type Error = Error;
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        let mut tx: Tx<USART> = Tx {
+            _usart: PhantomData,
+        };
+        serial::Write::<u8>::flush(&mut tx)
+    }
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+        let mut tx: Tx<USART> = Tx {
+            _usart: PhantomData,
+        };
+        tx.write(byte)
+    }
+}
+
+impl<USART, PINS> serial::Write<u16> for Serial<USART, PINS>
+where
+    USART: Instance,
+{
+    type Error = Error;
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        let mut tx: Tx<USART> = Tx {
+            _usart: PhantomData,
+        };
+        serial::Write::<u16>::flush(&mut tx)
+    }
+
+    fn write(&mut self, word: u16) -> nb::Result<(), Self::Error> {
+        let mut tx: Tx<USART> = Tx {
+            _usart: PhantomData,
+        };
+        tx.write(word)
+    }
+}
+
+impl<USART, TX> Serial<USART, (TX, NoRx)>
+where
+    TX: PinTx<USART>,
+    USART: Instance,
+{
+    /// Configures the USART for single-wire half-duplex communication
+    ///
+    /// Data is both sent and received on the TX pin, which should be set to
+    /// open-drain using `set_open_drain` and have an external pull-up.
+    /// Returns [`Error::BaudRate`] under the same conditions as
+    /// [`Serial::new`].
+    pub fn new_half_duplex(
+        usart: USART,
+        tx: TX,
+        clocks: Clocks,
+        config: Config,
+    ) -> Result<HalfDuplex<USART, TX>, Error> {
+        let mut serial = Self::new(usart, (tx, NoRx), clocks, config)?;
+        serial.reconfigure(|usart| usart.cr3.modify(|_, w| w.hdsel().set_bit()));
+
+        Ok(HalfDuplex { serial })
+    }
+}
+
+impl<USART, PINS> Serial<USART, PINS>
+where
+    PINS: Pins<USART>,
+    USART: Instance,
+{
+    /// Configures the USART as a synchronous master, driving a clock on `ck`
+    ///
+    /// The clock frequency is the configured baud rate. Only USART1, USART2,
+    /// USART3 and USART6 support synchronous mode. Returns
+    /// [`Error::BaudRate`] under the same conditions as [`Serial::new`].
+    pub fn new_synchronous<CK>(
+        usart: USART,
+        pins: PINS,
+        ck: CK,
+        clocks: Clocks,
+        config: Config,
+        sync_config: SynchronousConfig,
+    ) -> Result<Synchronous<USART, PINS, CK>, Error>
+    where
+        CK: PinCk<USART>,
+    {
+        let mut serial = Self::new(usart, pins, clocks, config)?;
+        serial.reconfigure(|usart| {
+            usart.cr2.modify(|_, w| {
+                w.cpol()
+                    .bit(sync_config.mode.polarity == Polarity::IdleHigh)
+                    .cpha()
+                    .bit(sync_config.mode.phase == Phase::CaptureOnSecondTransition)
+                    .lbcl()
+                    .bit(sync_config.last_bit_clock)
+                    .msbfirst()
+                    .bit(sync_config.msb_first)
+                    .clken()
+                    .set_bit()
+            })
+        });
+
+        Ok(Synchronous { serial, ck })
+    }
+}
+
+/// Synchronous USART master
+///
+/// Created by [`Serial::new_synchronous`]. Implements [`spi::FullDuplex`],
+/// with the bit order selected by [`SynchronousConfig::msb_first`].
+pub struct Synchronous<USART, PINS, CK> {
+    serial: Serial<USART, PINS>,
+    ck: CK,
+}
+
+impl<USART, PINS, CK> Synchronous<USART, PINS, CK>
+where
+    USART: Instance,
+    PINS: Pins<USART>,
+{
+    /// Starts listening for an interrupt event
+    pub fn listen(&mut self, event: Event) {
+        self.serial.listen(event);
+    }
+
+    /// End listening for an interrupt event
+    pub fn unlisten(&mut self, event: Event) {
+        self.serial.unlisten(event);
+    }
+
+    pub fn release(self) -> (USART, PINS, CK) {
+        let (usart, pins) = self.serial.release();
+        (usart, pins, self.ck)
+    }
+}
+
+impl<USART, PINS, CK> spi::FullDuplex<u8> for Synchronous<USART, PINS, CK>
+where
+    USART: Instance,
+{
+    type Error = Error;
+
+    fn read(&mut self) -> nb::Result<u8, Error> {
+        serial::Read::<u8>::read(&mut self.serial)
+    }
+
+    fn send(&mut self, byte: u8) -> nb::Result<(), Error> {
+        serial::Write::<u8>::write(&mut self.serial, byte)
+    }
+}
+
+impl<USART, PINS, CK> crate::hal::blocking::spi::transfer::Default<u8>
+    for Synchronous<USART, PINS, CK>
+where
+    USART: Instance,
+{
+}
+
+impl<USART, PINS, CK> crate::hal::blocking::spi::write::Default<u8> for Synchronous<USART, PINS, CK> where
+    USART: Instance
+{
+}
+
+impl<USART, PINS, CK> crate::hal::blocking::spi::write_iter::Default<u8>
+    for Synchronous<USART, PINS, CK>
+where
+    USART: Instance,
+{
+}
+
+/// Single-wire half-duplex serial
+///
+/// Created by [`Serial::new_half_duplex`]. The receiver is disabled while
+/// transmitting, so sent data isn't read back. It is enabled again once the
+/// transmission is complete, when flushing or on the next read.
+pub struct HalfDuplex<USART, TX> {
+    serial: Serial<USART, (TX, NoRx)>,
+}
+
+impl<USART, TX> HalfDuplex<USART, TX>
+where
+    USART: Instance,
+    TX: PinTx<USART>,
+{
+    /// Starts listening for an interrupt event
+    pub fn listen(&mut self, event: Event) {
+        self.serial.listen(event);
+    }
+
+    /// End listening for an interrupt event
+    pub fn unlisten(&mut self, event: Event) {
+        self.serial.unlisten(event);
+    }
+
+    pub fn release(self) -> (USART, TX) {
+        let (usart, (tx, NoRx)) = self.serial.release();
+        (usart, tx)
+    }
+
+    /// Switches to receiving once the last transmission is complete
+    fn turn_around(&mut self) -> nb::Result<(), Error> {
+        let usart = &self.serial.usart;
+
+        if usart.cr1.read().re().bit_is_clear() {
+            if usart.isr.read().tc().bit_is_clear() {
+                return Err(nb::Error::WouldBlock);
+            }
+            usart.cr1.modify(|_, w| w.re().enabled());
+        }
+
+        Ok(())
+    }
+}
+
+impl<USART, TX> serial::Read<u8> for HalfDuplex<USART, TX>
+where
+    USART: Instance,
+    TX: PinTx<USART>,
+{
+    type Error = Error;
+
+    fn read(&mut self) -> nb::Result<u8, Error> {
+        self.turn_around()?;
+        serial::Read::<u8>::read(&mut self.serial)
+    }
+}
+
+impl<USART, TX> serial::Write<u8> for HalfDuplex<USART, TX>
+where
+    USART: Instance,
+    TX: PinTx<USART>,
+{
+    type Error = Error;
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.turn_around()
+    }
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+        self.serial.usart.cr1.modify(|_, w| w.re().disabled());
+        serial::Write::<u8>::write(&mut self.serial, byte)
+    }
+}
+
+/// USART in LIN mode
+///
+/// Created by [`Serial::into_lin`].
+pub struct Lin<USART, PINS> {
+    serial: Serial<USART, PINS>,
+}
+
+impl<USART, PINS> Lin<USART, PINS>
+where
+    PINS: Pins<USART>,
+    USART: Instance,
+{
+    /// Sends a LIN break, starting a new frame
+    pub fn send_break(&mut self) {
+        self.serial.send_break();
+    }
+
+    /// Returns whether a LIN break has been detected
+    pub fn is_break_detected(&self) -> bool {
+        self.serial.usart.isr.read().lbdf().bit_is_set()
+    }
+
+    /// Clears the LIN break detection flag
+    pub fn clear_break_detected(&mut self) {
+        self.serial.usart.icr.write(|w| w.lbdcf().set_bit());
+    }
+
+    /// Starts listening for an interrupt event, e.g. [`Event::LinBreak`]
+    pub fn listen(&mut self, event: Event) {
+        self.serial.listen(event);
+    }
+
+    /// End listening for an interrupt event
+    pub fn unlisten(&mut self, event: Event) {
+        self.serial.unlisten(event);
+    }
+
+    /// Leaves LIN mode
+    pub fn into_serial(mut self) -> Serial<USART, PINS> {
+        self.serial.reconfigure(|usart| {
+            usart
+                .cr2
+                .modify(|_, w| w.linen().clear_bit().lbdie().clear_bit())
+        });
+        self.serial
+    }
+}
+
+/// USART in IrDA SIR mode
+///
+/// Created by [`Serial::into_irda`].
+pub struct Irda<USART, PINS> {
+    serial: Serial<USART, PINS>,
+}
+
+impl<USART, PINS> Irda<USART, PINS>
+where
+    PINS: Pins<USART>,
+    USART: Instance,
+{
+    /// Starts listening for an interrupt event
+    pub fn listen(&mut self, event: Event) {
+        self.serial.listen(event);
+    }
+
+    /// End listening for an interrupt event
+    pub fn unlisten(&mut self, event: Event) {
+        self.serial.unlisten(event);
+    }
+
+    /// Leaves IrDA mode
+    pub fn into_serial(mut self) -> Serial<USART, PINS> {
+        self.serial.reconfigure(|usart| {
+            usart
+                .cr3
+                .modify(|_, w| w.iren().clear_bit().irlp().clear_bit())
+        });
+        self.serial
+    }
+}
+
+/// USART in smartcard mode
+///
+/// Created by [`Serial::into_smartcard`].
+pub struct Smartcard<USART, PINS, CK> {
+    serial: Serial<USART, PINS>,
+    ck: CK,
+}
+
+impl<USART, PINS, CK> Smartcard<USART, PINS, CK>
+where
+    PINS: Pins<USART>,
+    USART: Instance,
+{
+    /// Sets the guard time, in baud clock periods
+    ///
+    /// The guard time is inserted after each transmitted character.
+    pub fn set_guard_time(&mut self, guard_time: u8) {
+        self.serial
+            .reconfigure(|usart| usart.gtpr.modify(|_, w| w.gt().bits(guard_time)));
+    }
+
+    /// Enables or disables the clock output on the CK pin
+    pub fn set_clock_output(&mut self, enabled: bool) {
+        self.serial
+            .reconfigure(|usart| usart.cr2.modify(|_, w| w.clken().bit(enabled)));
+    }
+
+    /// Starts listening for an interrupt event
+    pub fn listen(&mut self, event: Event) {
+        self.serial.listen(event);
+    }
+
+    /// End listening for an interrupt event
+    pub fn unlisten(&mut self, event: Event) {
+        self.serial.unlisten(event);
+    }
+
+    /// Leaves smartcard mode and returns the CK pin
+    ///
+    /// The frame format set by [`Serial::into_smartcard`] is kept.
+    pub fn into_serial(mut self) -> (Serial<USART, PINS>, CK) {
+        self.serial.reconfigure(|usart| {
+            usart.cr2.modify(|_, w| w.clken().clear_bit());
+            usart
+                .cr3
+                .modify(|_, w| w.scen().clear_bit().nack().clear_bit())
+        });
+        (self.serial, self.ck)
+    }
+}
+
+macro_rules! impl_mode_read_write {
+    ($($Mode:ident<$($T:ident),+>,)+) => {
+        $(
+            impl<USART, $($T),+> serial::Read<u8> for $Mode<USART, $($T),+>
+            where
+                USART: Instance,
+            {
+                type Error = Error;
+
+                fn read(&mut self) -> nb::Result<u8, Error> {
+                    serial::Read::<u8>::read(&mut self.serial)
+                }
+            }
+
+            impl<USART, $($T),+> serial::Write<u8> for $Mode<USART, $($T),+>
+            where
+                USART: Instance,
+            {
+                type Error = Error;
+
+                fn flush(&mut self) -> nb::Result<(), Self::Error> {
+                    serial::Write::<u8>::flush(&mut self.serial)
+                }
+
+                fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+                    serial::Write::<u8>::write(&mut self.serial, byte)
+                }
+            }
+        )+
+    }
+}
+
+impl_mode_read_write! {
+    Lin<PINS>,
+    Irda<PINS>,
+    Smartcard<PINS, CK>,
+}
+
+/// Storage for the transmit and receive buffers of a [`BufferedSerial`]
+///
+/// `TXN` and `RXN` are the buffer capacities, e.g. `heapless::consts::U64`.
+/// The storage has to outlive both halves of the buffered serial, so it is
+/// usually put into a `static`, e.g. using `cortex_m::singleton!`.
+pub struct SerialBuffers<TXN, RXN>
+where
+    TXN: ArrayLength<u8>,
+    RXN: ArrayLength<Result<u8, Error>>,
+{
+    tx: Queue<u8, TXN>,
+    rx: Queue<Result<u8, Error>, RXN>,
+    /// Set if received data was dropped because the receive buffer was full
+    overrun: AtomicBool,
+}
+
+impl<TXN, RXN> SerialBuffers<TXN, RXN>
+where
+    TXN: ArrayLength<u8>,
+    RXN: ArrayLength<Result<u8, Error>>,
+{
+    pub fn new() -> Self {
+        SerialBuffers {
+            tx: Queue::new(),
+            rx: Queue::new(),
+            overrun: AtomicBool::new(false),
+        }
+    }
+}
+
+impl<TXN, RXN> Default for SerialBuffers<TXN, RXN>
+where
+    TXN: ArrayLength<u8>,
+    RXN: ArrayLength<Result<u8, Error>>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Interrupt-driven serial with transmit and receive buffers
+///
+/// Created by [`Serial::into_buffered`], together with a
+/// [`BufferedSerialIsr`]. Reading and writing never block, but only access
+/// the buffers, which are emptied and filled by the interrupt handler.
+pub struct BufferedSerial<'a, USART, PINS, TXN, RXN>
+where
+    TXN: ArrayLength<u8>,
+    RXN: ArrayLength<Result<u8, Error>>,
+{
+    serial: Serial<USART, PINS>,
+    tx: Producer<'a, u8, TXN>,
+    rx: Consumer<'a, Result<u8, Error>, RXN>,
+    overrun: &'a AtomicBool,
+}
+
+/// The thread and interrupt handler sides returned by
+/// [`Serial::into_buffered`]
+pub type BufferedSerialHalves<'a, USART, PINS, TXN, RXN> = (
+    BufferedSerial<'a, USART, PINS, TXN, RXN>,
+    BufferedSerialIsr<'a, USART, TXN, RXN>,
+);
+
+/// Interrupt handler side of a [`BufferedSerial`]
+///
+/// Created by [`Serial::into_buffered`]. Move this into the USART interrupt
+/// handler and call [`on_interrupt`](BufferedSerialIsr::on_interrupt) there.
+pub struct BufferedSerialIsr<'a, USART, TXN, RXN>
+where
+    TXN: ArrayLength<u8>,
+    RXN: ArrayLength<Result<u8, Error>>,
+{
+    tx: Consumer<'a, u8, TXN>,
+    rx: Producer<'a, Result<u8, Error>, RXN>,
+    overrun: &'a AtomicBool,
+    _usart: PhantomData<USART>,
+}
+
+impl<'a, USART, TXN, RXN> BufferedSerialIsr<'a, USART, TXN, RXN>
+where
+    USART: Instance,
+    TXN: ArrayLength<u8>,
+    RXN: ArrayLength<Result<u8, Error>>,
+{
+    /// Handles the USART interrupt
+    ///
+    /// Moves received data into the receive buffer and pending data from the
+    /// transmit buffer to the USART. If the receive buffer is full, received
+    /// data is dropped and [`Error::Overrun`] is reported by the next read.
+    pub fn on_interrupt(&mut self) {
+        let mut rx: Rx<USART> = Rx {
+            _usart: PhantomData,
+        };
+        loop {
+            let result = match rx.read_word() {
+                Ok(word) => Ok(word as u8),
+                Err(nb::Error::Other(error)) => Err(error),
+                Err(nb::Error::WouldBlock) => break,
+            };
+            if self.rx.enqueue(result).is_err() {
+                self.overrun.store(true, Ordering::Release);
+            }
+        }
+
+        let mut tx: Tx<USART> = Tx {
+            _usart: PhantomData,
+        };
+        while let Some(&byte) = self.tx.peek() {
+            if tx.write_word(byte).is_err() {
+                break;
+            }
+            self.tx.dequeue();
         }
-    }
 
-    /// End listening for an interrupt event
-    pub fn unlisten(&mut self, event: Event) {
-        match event {
-            Event::Rxne => self.usart.cr1.modify(|_, w| w.rxneie().clear_bit()),
-            Event::Txe => self.usart.cr1.modify(|_, w| w.txeie().clear_bit()),
-            Event::CharacterMatch => self.usart.cr1.modify(|_, w| w.cmie().clear_bit()),
-            Event::Error => self.usart.cr3.modify(|_, w| w.eie().clear_bit()),
+        if !self.tx.ready() {
+            // NOTE(unsafe) The thread side only modifies CR1 in a critical
+            // section, so this interrupt handler has exclusive access.
+            let usart = unsafe { &*USART::ptr() };
+            usart.cr1.modify(|_, w| w.txeie().clear_bit());
+
+            // Data might have been added after the check above, before the
+            // interrupt was disabled
+            if self.tx.ready() {
+                usart.cr1.modify(|_, w| w.txeie().set_bit());
+            }
         }
     }
+}
 
-    pub fn split(self) -> (Tx<USART>, Rx<USART>) {
-        (
-            Tx {
-                _usart: PhantomData,
-            },
-            Rx {
-                _usart: PhantomData,
-            },
-        )
+impl<'a, USART, PINS, TXN, RXN> BufferedSerial<'a, USART, PINS, TXN, RXN>
+where
+    PINS: Pins<USART>,
+    USART: Instance,
+    TXN: ArrayLength<u8>,
+    RXN: ArrayLength<Result<u8, Error>>,
+{
+    /// Stops listening for interrupts and returns the serial
+    ///
+    /// Takes back the interrupt handler side too, as both share the buffers.
+    /// Data remaining in the buffers is discarded.
+    pub fn release(mut self, _isr: BufferedSerialIsr<'a, USART, TXN, RXN>) -> Serial<USART, PINS> {
+        self.serial.unlisten(Event::Rxne);
+        self.serial.unlisten(Event::Txe);
+        self.serial.unlisten(Event::Error);
+        self.serial
     }
 
-    pub fn release(self) -> (USART, PINS) {
-        (self.usart, self.pins)
+    /// Makes sure the interrupt handler sends the transmit buffer
+    fn start_sending(&mut self) {
+        // The interrupt handler modifies CR1 too
+        cortex_m::interrupt::free(|_| self.serial.listen(Event::Txe));
     }
 }
 
-impl<USART, PINS> serial::Read<u8> for Serial<USART, PINS>
+impl<'a, USART, PINS, TXN, RXN> serial::Read<u8> for BufferedSerial<'a, USART, PINS, TXN, RXN>
 where
+    PINS: Pins<USART>,
     USART: Instance,
+    TXN: ArrayLength<u8>,
+    RXN: ArrayLength<Result<u8, Error>>,
 {
     type Error = Error;
 
     fn read(&mut self) -> nb::Result<u8, Error> {
-        let mut rx: Rx<USART> = Rx {
-            _usart: PhantomData,
-        };
-        rx.read()
+        if self.overrun.swap(false, Ordering::Acquire) {
+            return Err(nb::Error::Other(Error::Overrun));
+        }
+        match self.rx.dequeue() {
+            Some(result) => result.map_err(nb::Error::Other),
+            None => Err(nb::Error::WouldBlock),
+        }
     }
 }
 
-impl<USART, PINS> serial::Write<u8> for Serial<USART, PINS>
+impl<'a, USART, PINS, TXN, RXN> serial::Write<u8> for BufferedSerial<'a, USART, PINS, TXN, RXN>
 where
+    PINS: Pins<USART>,
     USART: Instance,
+    TXN: ArrayLength<u8>,
+    RXN: ArrayLength<Result<u8, Error>>,
 {
     type Error = Error;
 
+    /// Returns `Ok` once the transmit buffer is empty and the last frame has
+    /// been sent
     fn flush(&mut self) -> nb::Result<(), Self::Error> {
-        let mut tx: Tx<USART> = Tx {
-            _usart: PhantomData,
-        };
-        tx.flush()
+        // The interrupt handler stops listening for TXE once the transmit
+        // buffer is empty
+        if self.serial.usart.cr1.read().txeie().bit_is_set() {
+            return Err(nb::Error::WouldBlock);
+        }
+        serial::Write::<u8>::flush(&mut self.serial)
     }
 
+    /// Adds a byte to the transmit buffer, returning `WouldBlock` if it is
+    /// full
     fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
-        let mut tx: Tx<USART> = Tx {
-            _usart: PhantomData,
-        };
-        tx.write(byte)
+        self.tx.enqueue(byte).map_err(|_| nb::Error::WouldBlock)?;
+        self.start_sending();
+        Ok(())
+    }
+}
+
+impl<'a, USART, PINS, TXN, RXN> fmt::Write for BufferedSerial<'a, USART, PINS, TXN, RXN>
+where
+    PINS: Pins<USART>,
+    USART: Instance,
+    TXN: ArrayLength<u8>,
+    RXN: ArrayLength<Result<u8, Error>>,
+{
+    /// Adds `s` to the transmit buffer
+    ///
+    /// Returns an error if `s` doesn't fit, in which case only the part of
+    /// `s` that fits has been added.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut result = Ok(());
+        for &byte in s.as_bytes() {
+            if self.tx.enqueue(byte).is_err() {
+                result = Err(fmt::Error);
+                break;
+            }
+        }
+        self.start_sending();
+        result
     }
 }
 
@@ -224,19 +1424,20 @@ pub struct Rx<USART> {
 impl<USART> Rx<USART>
 where
     USART: Instance,
-    Self: dma::Target,
 {
     /// Reads data using DMA until `buffer` is full
     ///
     /// DMA supports transfers up to 65535 bytes. If `buffer` is longer, this
     /// method will panic.
-    pub fn read_all<B>(
+    pub fn read_all<B, S>(
         self,
         buffer: Pin<B>,
-        dma: &dma::Handle<<Self as dma::Target>::Instance, state::Enabled>,
-        stream: <Self as dma::Target>::Stream,
-    ) -> dma::Transfer<Self, B, dma::Ready>
+        dma: &dma::Handle<S::Instance, state::Enabled>,
+        stream: S,
+    ) -> dma::Transfer<Self, S, B, dma::Ready>
     where
+        Self: dma::Target<S>,
+        S: dma::Stream,
         B: DerefMut + 'static,
         B::Target: AsMutSlice<Element = u8>,
     {
@@ -259,13 +1460,39 @@ where
     }
 }
 
-impl<USART> serial::Read<u8> for Rx<USART>
+impl<USART> Rx<USART>
 where
     USART: Instance,
 {
-    type Error = Error;
+    /// Returns whether an idle line has been detected
+    ///
+    /// Use this together with [`dma::Transfer::stop`] to finish a
+    /// [`read_all`](Rx::read_all) transfer of a variable-length frame early.
+    pub fn is_idle(&self) -> bool {
+        // NOTE(unsafe) atomic read with no side effects
+        unsafe { (*USART::ptr()).isr.read().idle().bit_is_set() }
+    }
 
-    fn read(&mut self) -> nb::Result<u8, Error> {
+    /// Clears the idle line flag
+    pub fn clear_idle(&mut self) {
+        // NOTE(unsafe) atomic write to stateless register
+        unsafe { (*USART::ptr()).icr.write(|w| w.idlecf().set_bit()) }
+    }
+
+    /// Returns whether the receiver timeout has expired
+    pub fn is_receiver_timeout(&self) -> bool {
+        // NOTE(unsafe) atomic read with no side effects
+        unsafe { (*USART::ptr()).isr.read().rtof().bit_is_set() }
+    }
+
+    /// Clears the receiver timeout flag
+    pub fn clear_receiver_timeout(&mut self) {
+        // NOTE(unsafe) atomic write to stateless register
+        unsafe { (*USART::ptr()).icr.write(|w| w.rtocf().set_bit()) }
+    }
+
+    /// Reads a data word, with the parity bit masked out
+    fn read_word(&mut self) -> nb::Result<u16, Error> {
         // NOTE(unsafe) atomic read with no side effects
         let isr = unsafe { (*USART::ptr()).isr.read() };
 
@@ -291,17 +1518,214 @@ where
 
         if isr.rxne().bit_is_set() {
             // NOTE(unsafe): Atomic read with no side effects
-            return Ok(unsafe {
-                // Casting to `u8` should be fine, as we've configured the USART
-                // to use 8 data bits.
-                (*USART::ptr()).rdr.read().rdr().bits() as u8
-            });
+            let (cr1, rdr) = unsafe {
+                (
+                    (*USART::ptr()).cr1.read(),
+                    (*USART::ptr()).rdr.read().rdr().bits(),
+                )
+            };
+
+            // The word length configured in M includes the parity bit, which
+            // is received as the most significant bit
+            let word_length = match (cr1.m1().bit_is_set(), cr1.m0().bit_is_set()) {
+                (true, _) => 7,
+                (false, false) => 8,
+                (false, true) => 9,
+            };
+            let data_bits = word_length - cr1.pce().bit_is_set() as u16;
+
+            return Ok(rdr & ((1 << data_bits) - 1));
         }
 
         Err(nb::Error::WouldBlock)
     }
 }
 
+impl<USART> Rx<USART>
+where
+    USART: Instance,
+{
+    /// Receives data continuously into `buffer`, using circular DMA
+    ///
+    /// The returned [`CircularRx`] works like a ring buffer that is filled
+    /// by DMA in the background, so no data is lost between reads as long
+    /// as it is emptied in time. Listening for [`Event::Idle`] is a good way
+    /// to be notified when a burst of data is ready to be read.
+    ///
+    /// DMA supports buffers up to 65535 bytes. If `buffer` is empty or
+    /// longer than that, this method will panic.
+    pub fn read_circular<B, S>(
+        self,
+        buffer: Pin<B>,
+        dma: &dma::Handle<S::Instance, state::Enabled>,
+        stream: S,
+    ) -> CircularRx<USART, S, B>
+    where
+        Self: dma::Target<S>,
+        S: dma::Stream,
+        B: DerefMut + 'static,
+        B::Target: AsMutSlice<Element = u8>,
+    {
+        assert!(!buffer.as_slice().is_empty());
+
+        let mut transfer = self.read_all(buffer, dma, stream);
+        transfer.enable_circular(dma);
+
+        CircularRx {
+            transfer: transfer.start(dma),
+            read_pos: 0,
+            laps: 0,
+        }
+    }
+}
+
+/// Continuous serial reception into a circular DMA buffer
+///
+/// Created by [`Rx::read_circular`]. There is a single consumer, the DMA
+/// stream being the producer, so no locking is required.
+pub struct CircularRx<USART, S, B>
+where
+    Rx<USART>: dma::Target<S>,
+    S: dma::Stream,
+{
+    transfer: dma::Transfer<Rx<USART>, S, B, dma::Started>,
+    /// Position of the next byte to read in the buffer
+    read_pos: usize,
+    /// How many times the DMA write position wrapped around without the read
+    /// position following it
+    laps: usize,
+}
+
+impl<USART, S, B> CircularRx<USART, S, B>
+where
+    USART: Instance,
+    Rx<USART>: dma::Target<S>,
+    S: dma::Stream,
+    B: Deref,
+    B::Target: AsSlice<Element = u8>,
+{
+    /// Returns the number of bytes that can be read
+    ///
+    /// Returns [`Error::Overrun`] if the DMA stream has overwritten data that
+    /// wasn't read yet. The buffer is emptied in that case.
+    ///
+    /// Only a single wrap-around of the DMA stream can be detected between
+    /// two calls. To reliably detect an overrun, this method must be called
+    /// at least once per buffer length of received data.
+    pub fn available(
+        &mut self,
+        dma: &dma::Handle<S::Instance, state::Enabled>,
+    ) -> Result<usize, Error> {
+        let len = self.transfer.len();
+
+        // Make sure NDTR and the transfer complete flag are consistent, in
+        // case the DMA stream wraps around while we're looking at them
+        let write_pos = loop {
+            let wrapped = self.transfer.take_transfer_complete(dma);
+            // NDTR might read as 0 right before it is reloaded
+            let write_pos = (len - self.transfer.remaining(dma)) % len;
+
+            if wrapped {
+                self.laps += 1;
+            }
+            if !self.transfer.take_transfer_complete(dma) {
+                break write_pos;
+            }
+            self.laps += 1;
+        };
+
+        // This is only ever too low, never too high, if the transfer complete
+        // flag lags behind NDTR
+        let available = (self.laps * len + write_pos).saturating_sub(self.read_pos);
+        if available > len {
+            self.read_pos = write_pos;
+            self.laps = 0;
+            return Err(Error::Overrun);
+        }
+
+        Ok(available)
+    }
+
+    /// Reads as many bytes as are available and fit into `buffer`
+    ///
+    /// Returns the number of bytes read, or [`Error::Overrun`] as described
+    /// in [`available`](CircularRx::available).
+    pub fn read(
+        &mut self,
+        dma: &dma::Handle<S::Instance, state::Enabled>,
+        buffer: &mut [u8],
+    ) -> Result<usize, Error> {
+        let n = self.available(dma)?.min(buffer.len());
+        let len = self.transfer.len();
+        let ptr = self.transfer.buffer().as_slice().as_ptr();
+
+        for byte in &mut buffer[..n] {
+            // NOTE(unsafe) `read_pos` is always within the buffer. The DMA
+            // stream may be writing to other parts of it.
+            *byte = unsafe { ptr::read_volatile(ptr.add(self.read_pos)) };
+
+            self.read_pos += 1;
+            if self.read_pos == len {
+                self.read_pos = 0;
+                self.laps -= 1;
+            }
+        }
+
+        // Make sure the bytes we read haven't been overwritten in the meantime
+        self.available(dma)?;
+
+        Ok(n)
+    }
+
+    /// Returns whether an idle line has been detected
+    pub fn is_idle(&self) -> bool {
+        Rx::<USART> {
+            _usart: PhantomData,
+        }
+        .is_idle()
+    }
+
+    /// Clears the idle line flag
+    pub fn clear_idle(&mut self) {
+        Rx::<USART> {
+            _usart: PhantomData,
+        }
+        .clear_idle()
+    }
+
+    /// Stops the reception and returns the resources
+    pub fn stop(
+        self,
+        dma: &dma::Handle<S::Instance, state::Enabled>,
+    ) -> dma::TransferResources<Rx<USART>, S, B> {
+        self.transfer.stop(dma).0
+    }
+}
+
+impl<USART> serial::Read<u8> for Rx<USART>
+where
+    USART: Instance,
+{
+    type Error = Error;
+
+    fn read(&mut self) -> nb::Result<u8, Error> {
+        // With 9 data bits, the most significant bit is dropped. Use
+        // `Read<u16>` for this configuration.
+        self.read_word().map(|word| word as u8)
+    }
+}
+
+impl<USART> serial::Read<u16> for Rx<USART>
+where
+    USART: Instance,
+{
+    type Error = Error;
+
+    fn read(&mut self) -> nb::Result<u16, Error> {
+        self.read_word()
+    }
+}
+
 /// Serial transmitter
 pub struct Tx<USART> {
     _usart: PhantomData<USART>,
@@ -309,20 +1733,21 @@ pub struct Tx<USART> {
 
 impl<USART> Tx<USART>
 where
-    Self: dma::Target,
     USART: Instance,
 {
     /// Writes data using DMA
     ///
     /// DMA supports transfers up to 65535 bytes. If `data` is longer, this
     /// method will panic.
-    pub fn write_all<B>(
+    pub fn write_all<B, S>(
         self,
         data: Pin<B>,
-        dma: &dma::Handle<<Self as dma::Target>::Instance, state::Enabled>,
-        stream: <Self as dma::Target>::Stream,
-    ) -> dma::Transfer<Self, B, dma::Ready>
+        dma: &dma::Handle<S::Instance, state::Enabled>,
+        stream: S,
+    ) -> dma::Transfer<Self, S, B, dma::Ready>
     where
+        Self: dma::Target<S>,
+        S: dma::Stream,
         B: Deref + 'static,
         B::Target: AsSlice<Element = u8>,
     {
@@ -348,13 +1773,11 @@ where
     }
 }
 
-impl<USART> serial::Write<u8> for Tx<USART>
+impl<USART> Tx<USART>
 where
     USART: Instance,
 {
-    type Error = Error;
-
-    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+    fn flush_word(&mut self) -> nb::Result<(), Error> {
         // NOTE(unsafe) atomic read with no side effects
         let isr = unsafe { (*USART::ptr()).isr.read() };
 
@@ -365,14 +1788,15 @@ where
         }
     }
 
-    fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+    fn write_word<Word>(&mut self, word: Word) -> nb::Result<(), Error> {
         // NOTE(unsafe) atomic read with no side effects
         let isr = unsafe { (*USART::ptr()).isr.read() };
 
         if isr.txe().bit_is_set() {
             // NOTE(unsafe) atomic write to stateless register
-            // NOTE(write_volatile) 8-bit write that's not possible through the svd2rust API
-            unsafe { ptr::write_volatile(&(*USART::ptr()).tdr as *const _ as *mut _, byte) }
+            // NOTE(write_volatile) 8-bit or 16-bit write that's not possible
+            // through the svd2rust API
+            unsafe { ptr::write_volatile(&(*USART::ptr()).tdr as *const _ as *mut _, word) }
             Ok(())
         } else {
             Err(nb::Error::WouldBlock)
@@ -380,24 +1804,277 @@ where
     }
 }
 
+impl<USART> serial::Write<u8> for Tx<USART>
+where
+    USART: Instance,
+{
+    type Error = Error;
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.flush_word()
+    }
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), Self::Error> {
+        self.write_word(byte)
+    }
+}
+
+impl<USART> serial::Write<u16> for Tx<USART>
+where
+    USART: Instance,
+{
+    type Error = Error;
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.flush_word()
+    }
+
+    /// Writes a data word. Bits beyond the configured word length are
+    /// ignored.
+    fn write(&mut self, word: u16) -> nb::Result<(), Self::Error> {
+        self.write_word(word)
+    }
+}
+
 /// USART configuration
 pub struct Config {
     pub baud_rate: Bps,
     pub oversampling: Oversampling,
     pub character_match: Option<u8>,
+    pub word_length: WordLength,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub clock_source: ClockSource,
+    pub flow_control: FlowControl,
+    pub driver_enable: Option<DriverEnable>,
+    /// Receiver timeout, in bit periods after the last stop bit (at most
+    /// 2^24 - 1). See [`Event::ReceiverTimeout`].
+    pub receiver_timeout: Option<u32>,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Oversampling {
     By8,
     By16,
 }
 
+/// Hardware flow control
+///
+/// The RTS and CTS pins must be passed to [`Serial::new`] as part of a
+/// `(TX, RX, RTS, CTS)` tuple. Use [`NoRts`] or [`NoCts`] for a pin that isn't
+/// used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FlowControl {
+    None,
+    /// RTS is asserted while there is room in the receive buffer
+    Rts,
+    /// Transmission is held while CTS is deasserted
+    Cts,
+    RtsCts,
+}
+
+/// RS-485 driver enable configuration
+///
+/// The DE pin must be passed to [`Serial::new`] as part of a `(TX, RX, DE)`
+/// tuple. It is asserted by the hardware while a frame is being transmitted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DriverEnable {
+    pub polarity: DriverEnablePolarity,
+    /// Time between the activation of DE and the start bit, in sample times
+    /// (1/8 or 1/16 bit, depending on oversampling). At most 31.
+    pub assertion_time: u8,
+    /// Time between the end of the last stop bit and the deactivation of DE,
+    /// in sample times. At most 31.
+    pub deassertion_time: u8,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DriverEnablePolarity {
+    ActiveHigh,
+    ActiveLow,
+}
+
+impl Default for DriverEnable {
+    fn default() -> Self {
+        Self {
+            polarity: DriverEnablePolarity::ActiveHigh,
+            assertion_time: 0,
+            deassertion_time: 0,
+        }
+    }
+}
+
+/// Kernel clock of the USART, from which the baud rate is generated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClockSource {
+    /// APB clock, PCLK1 or PCLK2 depending on the instance
+    Pclk,
+    /// System clock
+    Sysclk,
+    /// HSI oscillator (16 MHz), which must be running
+    Hsi,
+    /// LSE oscillator (32.768 kHz), which must be running
+    Lse,
+}
+
+/// Synchronous mode configuration
+#[derive(Clone, Copy)]
+pub struct SynchronousConfig {
+    /// Clock polarity and phase
+    pub mode: spi::Mode,
+    /// Output a clock pulse for the last data bit
+    pub last_bit_clock: bool,
+    /// Send and receive the most significant bit first, as most SPI devices
+    /// expect, instead of the least significant bit
+    pub msb_first: bool,
+}
+
+impl Default for SynchronousConfig {
+    fn default() -> Self {
+        Self {
+            mode: spi::MODE_0,
+            last_bit_clock: true,
+            msb_first: true,
+        }
+    }
+}
+
+/// Character that is used for automatic baud rate detection
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AutoBaudMode {
+    /// Any character starting with a 1 bit, measuring the start bit
+    StartBit,
+    /// Any character starting with a 10xx bit pattern, measuring from falling
+    /// edge to falling edge
+    FallingEdge,
+    /// The 0x7F character
+    Frame0x7F,
+    /// The 0x55 character
+    Frame0x55,
+}
+
+/// Length of the LIN break that is detected
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LinBreakLength {
+    Bits10,
+    Bits11,
+}
+
+/// IrDA SIR mode
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IrdaMode {
+    /// Pulses are 3/16 of a bit period wide
+    Normal,
+    /// Pulses are 3 periods of the low-power clock wide, which is the kernel
+    /// clock divided by `prescaler`. The low-power clock should be around
+    /// 1.8432 MHz.
+    LowPower { prescaler: u8 },
+}
+
+/// Smartcard mode configuration
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SmartcardConfig {
+    /// Guard time after each transmitted character, in baud clock periods
+    pub guard_time: u8,
+    /// The smartcard clock on CK is the kernel clock divided by
+    /// `2 * prescaler`. Between 1 and 31.
+    pub prescaler: u8,
+    /// Send a NACK when a parity error is detected
+    pub nack: bool,
+    /// Number of automatic retransmissions when a NACK is received, or of
+    /// receive attempts when a parity error is detected. At most 7.
+    pub retries: u8,
+}
+
+impl Default for SmartcardConfig {
+    fn default() -> Self {
+        Self {
+            guard_time: 0,
+            prescaler: 1,
+            nack: true,
+            retries: 3,
+        }
+    }
+}
+
+/// Computes the BRR value for `baud_rate`, rounding USARTDIV to nearest
+fn calculate_brr(
+    kernel_clock: u32,
+    baud_rate: u32,
+    oversampling: Oversampling,
+) -> Result<u32, Error> {
+    let kernel_clock = kernel_clock as u64;
+    let baud_rate = baud_rate as u64;
+    if baud_rate == 0 {
+        return Err(Error::BaudRate);
+    }
+
+    let clock = match oversampling {
+        Oversampling::By8 => 2 * kernel_clock,
+        Oversampling::By16 => kernel_clock,
+    };
+    let usart_div = (clock + baud_rate / 2) / baud_rate;
+    if !(16..=0xffff).contains(&usart_div) {
+        return Err(Error::BaudRate);
+    }
+
+    let actual = clock / usart_div;
+    let deviation = actual.abs_diff(baud_rate);
+    if deviation * 1000 > baud_rate * BAUD_RATE_TOLERANCE as u64 {
+        return Err(Error::BaudRate);
+    }
+
+    let usart_div = usart_div as u32;
+    Ok(match oversampling {
+        // BRR[3] must be kept cleared, BRR[2:0] is USARTDIV[3:0] shifted
+        Oversampling::By8 => usart_div & 0xfff0 | (usart_div & 0x000f) >> 1,
+        Oversampling::By16 => usart_div,
+    })
+}
+
+/// Number of data bits, not including the parity bit
+///
+/// 9 data bits can't be combined with parity. Use `Read<u16>`/`Write<u16>`
+/// to transfer 9-bit words.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WordLength {
+    DataBits7,
+    DataBits8,
+    DataBits9,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Parity {
+    ParityNone,
+    ParityEven,
+    ParityOdd,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopBits {
+    /// 0.5 stop bits, for smartcard mode
+    STOP0P5,
+    /// 1 stop bit
+    STOP1,
+    /// 1.5 stop bits, for smartcard mode
+    STOP1P5,
+    /// 2 stop bits
+    STOP2,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             baud_rate: 115_200.bps(),
             oversampling: Oversampling::By16,
             character_match: None,
+            word_length: WordLength::DataBits8,
+            parity: Parity::ParityNone,
+            stop_bits: StopBits::STOP1,
+            clock_source: ClockSource::Sysclk,
+            flow_control: FlowControl::None,
+            driver_enable: None,
+            receiver_timeout: None,
         }
     }
 }
@@ -411,20 +2088,27 @@ pub enum Event {
     Txe,
     /// Character match interrupt
     CharacterMatch,
-    /// Error interrupt
+    /// The line has been idle for one frame after receiving data
+    Idle,
+    /// Nothing has been received for the configured receiver timeout
+    ReceiverTimeout,
+    /// A LIN break has been detected, see [`Lin`]
+    LinBreak,
+    /// Error interrupt, including parity errors
     Error,
 }
 
 /// Implemented by all USART instances
 pub trait Instance: Deref<Target = pac::usart1::RegisterBlock> {
     fn ptr() -> *const pac::usart1::RegisterBlock;
-    fn select_sysclock(rcc: &pac::rcc::RegisterBlock);
+    fn select_clock(rcc: &pac::rcc::RegisterBlock, source: ClockSource);
     fn enable_clock(rcc: &pac::rcc::RegisterBlock);
+    fn pclk(clocks: &Clocks) -> Hertz;
 }
 
 macro_rules! impl_instance {
     ($(
-        $USARTX:ident: ($apbXenr:ident, $usartXsel:ident, $usartXen:ident),
+        $USARTX:ident: ($apbXenr:ident, $usartXsel:ident, $usartXen:ident, $pclkX:ident),
     )+) => {
         $(
             impl Instance for $USARTX {
@@ -432,13 +2116,23 @@ macro_rules! impl_instance {
                     $USARTX::ptr()
                 }
 
-                fn select_sysclock(rcc: &pac::rcc::RegisterBlock) {
-                    rcc.dckcfgr2.modify(|_, w| w.$usartXsel().bits(1));
+                fn select_clock(rcc: &pac::rcc::RegisterBlock, source: ClockSource) {
+                    let sel = match source {
+                        ClockSource::Pclk => 0,
+                        ClockSource::Sysclk => 1,
+                        ClockSource::Hsi => 2,
+                        ClockSource::Lse => 3,
+                    };
+                    rcc.dckcfgr2.modify(|_, w| w.$usartXsel().bits(sel));
                 }
 
                 fn enable_clock(rcc: &pac::rcc::RegisterBlock) {
                     rcc.$apbXenr.modify(|_, w| w.$usartXen().set_bit());
                 }
+
+                fn pclk(clocks: &Clocks) -> Hertz {
+                    clocks.$pclkX()
+                }
             }
         )+
     }
@@ -446,13 +2140,14 @@ macro_rules! impl_instance {
 
 #[cfg(any(feature = "device-selected",))]
 impl_instance! {
-    USART1: (apb2enr, usart1sel, usart1en),
-    USART2: (apb1enr, usart2sel, usart2en),
-    USART3: (apb1enr, usart3sel, usart3en),
-    UART4:  (apb1enr, uart4sel,  uart4en),
-    UART5:  (apb1enr, uart5sel,  uart5en),
-    USART6: (apb2enr, usart6sel, usart6en),
-    UART7:  (apb1enr, uart7sel,  uart7en),
+    USART1: (apb2enr, usart1sel, usart1en, pclk2),
+    USART2: (apb1enr, usart2sel, usart2en, pclk1),
+    USART3: (apb1enr, usart3sel, usart3en, pclk1),
+    UART4:  (apb1enr, uart4sel,  uart4en,  pclk1),
+    UART5:  (apb1enr, uart5sel,  uart5en,  pclk1),
+    USART6: (apb2enr, usart6sel, usart6en, pclk2),
+    UART7:  (apb1enr, uart7sel,  uart7en,  pclk1),
+    UART8:  (apb1enr, uart8sel,  uart8en,  pclk1),
 }
 
 impl<USART> fmt::Write for Tx<USART>
@@ -468,3 +2163,64 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{calculate_brr, Oversampling};
+
+    #[test]
+    fn brr_oversampling_by_16() {
+        assert_eq!(
+            calculate_brr(216_000_000, 115_200, Oversampling::By16).unwrap(),
+            1875
+        );
+        assert_eq!(
+            calculate_brr(108_000_000, 3_000_000, Oversampling::By16).unwrap(),
+            36
+        );
+        // 117.19 is rounded down
+        assert_eq!(
+            calculate_brr(108_000_000, 921_600, Oversampling::By16).unwrap(),
+            117
+        );
+        // 29.30 is rounded down, 29.5 would be rounded up
+        assert_eq!(
+            calculate_brr(27_000_000, 921_600, Oversampling::By16).unwrap(),
+            29
+        );
+        assert_eq!(
+            calculate_brr(59_000_000, 2_000_000, Oversampling::By16).unwrap(),
+            30
+        );
+    }
+
+    #[test]
+    fn brr_oversampling_by_8() {
+        // USARTDIV = 0x4b, BRR[2:0] = USARTDIV[3:0] >> 1
+        assert_eq!(
+            calculate_brr(54_000_000, 1_440_000, Oversampling::By8).unwrap(),
+            0x45
+        );
+        assert_eq!(
+            calculate_brr(216_000_000, 27_000_000, Oversampling::By8).unwrap(),
+            0x10
+        );
+    }
+
+    #[test]
+    fn brr_out_of_range() {
+        // USARTDIV < 16
+        assert!(calculate_brr(16_000_000, 3_000_000, Oversampling::By16).is_err());
+        // USARTDIV > 0xffff
+        assert!(calculate_brr(216_000_000, 300, Oversampling::By16).is_err());
+        assert!(calculate_brr(216_000_000, 0, Oversampling::By16).is_err());
+    }
+
+    #[test]
+    fn brr_tolerance() {
+        // 16 MHz / 17 = 941176 Baud, 2.1% off
+        assert!(calculate_brr(16_000_000, 921_600, Oversampling::By16).is_err());
+        // 16 MHz * 2 / 35 = 914285 Baud, 0.8% off
+        assert!(calculate_brr(16_000_000, 921_600, Oversampling::By8).is_ok());
+    }
+}
@@ -5,23 +5,26 @@
 pub use crate::pac::spi1::cr1::BR_A as ClockDivider;
 pub use embedded_hal::spi::{Mode, Phase, Polarity};
 
-use core::{fmt, marker::PhantomData, ops::DerefMut, pin::Pin, ptr};
+use core::{fmt, marker::PhantomData, mem::ManuallyDrop, ops::DerefMut, pin::Pin, ptr};
 
 use as_slice::{AsMutSlice, AsSlice as _};
 use embedded_hal::{
     blocking::spi::{transfer, write, write_iter},
-    spi::FullDuplex,
+    spi::{FullDuplex, MODE_0},
 };
 
 use crate::{
     gpio::{self, Alternate, AF5, AF6, AF7},
     pac::{self, spi1::cr2},
-    rcc::Rcc,
+    rcc::{Clocks, Rcc},
     state,
+    time::Hertz,
 };
 
 use crate::dma;
 
+pub mod bus;
+
 /// Entry point to the SPI API
 pub struct Spi<I, P, State> {
     spi: I,
@@ -44,6 +47,10 @@ where
     }
 
     /// Initialize the SPI peripheral
+    ///
+    /// `Word` is either `u8` or `u16`, for 8-bit or 16-bit frames, and can
+    /// usually be inferred. Use [`Spi::enable_with_word_size`] for the other
+    /// frame sizes.
     pub fn enable<Word>(
         self,
         rcc: &mut Rcc,
@@ -51,27 +58,264 @@ where
         mode: Mode,
     ) -> Spi<I, P, Enabled<Word>>
     where
-        Word: SupportedWordSize,
+        Word: SupportedWord,
+    {
+        self.enable_with_word_size::<Word>(rcc, clock_divider, mode)
+    }
+
+    /// Initialize the SPI peripheral with the data frame size selected by
+    /// `Size`
+    ///
+    /// `Size` is one of the markers like [`Bits12`], or `u8`/`u16`. Frames
+    /// are exchanged as [`SupportedWordSize::Word`].
+    pub fn enable_with_word_size<Size>(
+        self,
+        rcc: &mut Rcc,
+        clock_divider: ClockDivider,
+        mode: Mode,
+    ) -> Spi<I, P, EnabledWordSize<Size>>
+    where
+        Size: SupportedWordSize,
     {
-        let cpol = mode.polarity == Polarity::IdleHigh;
-        let cpha = mode.phase == Phase::CaptureOnSecondTransition;
+        self.configure::<Size, _>(
+            rcc,
+            Settings {
+                nss_output: P::NSS,
+                ..Settings::new(clock_divider.into(), mode)
+            },
+            Enabled(PhantomData),
+        )
+    }
+
+    /// Initialize the SPI peripheral with a clock of at most `frequency`
+    ///
+    /// Picks the fastest clock divider that doesn't exceed `frequency`, based
+    /// on the clock of the bus the instance is connected to. Returns the
+    /// enabled SPI together with the actual SCK frequency.
+    ///
+    /// If `frequency` is lower than the bus clock divided by 256, the slowest
+    /// clock divider is used, and the returned frequency is higher than
+    /// requested.
+    pub fn enable_with_frequency<Size>(
+        self,
+        rcc: &mut Rcc,
+        clocks: &Clocks,
+        frequency: Hertz,
+        mode: Mode,
+    ) -> (Spi<I, P, EnabledWordSize<Size>>, Hertz)
+    where
+        Size: SupportedWordSize,
+    {
+        let (br, actual) = calculate_br(I::clock(clocks).0, frequency.0);
+
+        let spi = self.configure::<Size, _>(
+            rcc,
+            Settings {
+                nss_output: P::NSS,
+                ..Settings::new(br, mode)
+            },
+            Enabled(PhantomData),
+        );
+        (spi, Hertz(actual))
+    }
+}
 
+impl<I, P> Spi<I, P, state::Disabled>
+where
+    I: Instance,
+{
+    /// Enables the peripheral clock and applies `settings`
+    fn configure<Size, State>(
+        self,
+        rcc: &mut Rcc,
+        settings: Settings,
+        state: State,
+    ) -> Spi<I, P, State>
+    where
+        Size: SupportedWordSize,
+    {
         self.spi.enable_clock(rcc);
-        self.spi.configure::<Word>(clock_divider.into(), cpol, cpha);
+        self.spi.configure::<Size>(settings);
 
         Spi {
             spi: self.spi,
             pins: self.pins,
-            _state: Enabled(PhantomData),
+            _state: state,
+        }
+    }
+}
+
+/// Finds the fastest baud rate divider that doesn't exceed `frequency`
+///
+/// Returns the BR field value and the resulting frequency. Falls back to the
+/// slowest divider, if even that exceeds `frequency`.
+fn calculate_br(bus_clock: u32, frequency: u32) -> (u8, u32) {
+    // The divider is 2^(BR + 1)
+    (0..8)
+        .map(|br| (br, bus_clock >> (br + 1)))
+        .find(|&(_, actual)| actual <= frequency)
+        .unwrap_or((7, bus_clock >> 8))
+}
+
+impl<I, SCK, MISO, MOSI, NSS> Spi<I, (SCK, MISO, MOSI, NSS), state::Disabled>
+where
+    I: Instance,
+    SCK: Sck<I>,
+    MISO: Miso<I>,
+    MOSI: Mosi<I>,
+    NSS: Nss<I>,
+{
+    /// Initialize the SPI peripheral in slave mode
+    ///
+    /// The clock is provided by the master, which selects the slave using the
+    /// hardware NSS input. Words that are sent before NSS is asserted are
+    /// pre-loaded into the TX FIFO and sent in the first frames.
+    pub fn enable_slave<Size>(
+        self,
+        rcc: &mut Rcc,
+        mode: Mode,
+    ) -> Spi<I, (SCK, MISO, MOSI, NSS), EnabledWordSize<Size, Slave>>
+    where
+        Size: SupportedWordSize,
+    {
+        self.configure::<Size, _>(
+            rcc,
+            Settings {
+                slave: true,
+                ..Settings::new(0, mode)
+            },
+            Enabled(PhantomData),
+        )
+    }
+
+    /// Initialize the SPI peripheral as master, using the TI frame format
+    ///
+    /// The NSS pin is driven as the frame synchronization signal. Clock
+    /// polarity and phase are defined by the frame format.
+    pub fn enable_ti<Size>(
+        self,
+        rcc: &mut Rcc,
+        clock_divider: ClockDivider,
+    ) -> Spi<I, (SCK, MISO, MOSI, NSS), EnabledWordSize<Size>>
+    where
+        Size: SupportedWordSize,
+    {
+        self.configure::<Size, _>(
+            rcc,
+            Settings {
+                nss_output: true,
+                ti: true,
+                ..Settings::new(clock_divider.into(), MODE_0)
+            },
+            Enabled(PhantomData),
+        )
+    }
+}
+
+impl<I, SCK, MOSI> Spi<I, (SCK, NoMiso, MOSI), state::Disabled>
+where
+    I: Instance,
+    SCK: Sck<I>,
+    MOSI: Mosi<I>,
+{
+    /// Initialize the SPI peripheral as master with a single bidirectional
+    /// data line (3-wire mode)
+    ///
+    /// The MOSI pin is used for both sending and receiving.
+    pub fn enable_bidirectional<Size>(
+        self,
+        rcc: &mut Rcc,
+        clock_divider: ClockDivider,
+        mode: Mode,
+    ) -> Spi<I, (SCK, NoMiso, MOSI), Bidirectional<Size::Word>>
+    where
+        Size: SupportedWordSize,
+    {
+        self.configure::<Size, _>(
+            rcc,
+            Settings {
+                line_mode: LineMode::Bidirectional,
+                ..Settings::new(clock_divider.into(), mode)
+            },
+            Bidirectional(PhantomData),
+        )
+    }
+}
+
+impl<I, SCK, MISO> Spi<I, (SCK, MISO, NoMosi), state::Disabled>
+where
+    I: Instance,
+    SCK: Sck<I>,
+    MISO: Miso<I>,
+{
+    /// Initialize the SPI peripheral as a receive-only master
+    pub fn enable_receive_only<Size>(
+        self,
+        rcc: &mut Rcc,
+        clock_divider: ClockDivider,
+        mode: Mode,
+    ) -> Spi<I, (SCK, MISO, NoMosi), ReceiveOnly<Size::Word>>
+    where
+        Size: SupportedWordSize,
+    {
+        self.configure::<Size, _>(
+            rcc,
+            Settings {
+                line_mode: LineMode::ReceiveOnly,
+                ..Settings::new(clock_divider.into(), mode)
+            },
+            ReceiveOnly(PhantomData),
+        )
+    }
+}
+
+impl<I, P, Word> Spi<I, P, Bidirectional<Word>>
+where
+    I: Instance,
+    P: Pins<I>,
+    Word: SupportedWord,
+{
+    /// Sends `words` on the data line
+    pub fn write(&mut self, words: &[Word]) -> Result<(), Error> {
+        self.spi.set_output(true);
+        self.spi.enable();
+
+        for &word in words {
+            if let Err(err) = nb::block!(self.spi.send(word)) {
+                self.spi.disable();
+                return Err(err);
+            }
         }
+
+        // Waits for the last frame to be sent
+        self.spi.disable();
+        Ok(())
+    }
+
+    /// Fills `buffer` with words received on the data line
+    pub fn read(&mut self, buffer: &mut [Word]) -> Result<(), Error> {
+        self.spi.set_output(false);
+        self.spi.receive(buffer)
+    }
+}
+
+impl<I, P, Word> Spi<I, P, ReceiveOnly<Word>>
+where
+    I: Instance,
+    P: Pins<I>,
+    Word: SupportedWord,
+{
+    /// Fills `buffer` with received words
+    pub fn read(&mut self, buffer: &mut [Word]) -> Result<(), Error> {
+        self.spi.receive(buffer)
     }
 }
 
-impl<I, P, Word> Spi<I, P, Enabled<Word>>
+impl<I, P, Word, Role> Spi<I, P, Enabled<Word, Role>>
 where
     I: Instance,
     P: Pins<I>,
-    Word: SupportedWordSize,
+    Word: SupportedWord,
 {
     /// Start an SPI transfer using DMA
     ///
@@ -93,17 +337,19 @@ where
     /// would be nice to simplify that, but I believe that requires an equality
     /// constraint in the where clause, which is not supported yet by the
     /// compiler.
-    pub fn transfer_all<B>(
+    pub fn transfer_all<B, RxStream, TxStream>(
         self,
         buffer: Pin<B>,
-        dma_rx: &dma::Handle<<Rx<I> as dma::Target>::Instance, state::Enabled>,
-        dma_tx: &dma::Handle<<Tx<I> as dma::Target>::Instance, state::Enabled>,
-        rx: <Rx<I> as dma::Target>::Stream,
-        tx: <Tx<I> as dma::Target>::Stream,
-    ) -> Transfer<Word, I, P, B, Rx<I>, Tx<I>, dma::Ready>
+        dma_rx: &dma::Handle<RxStream::Instance, state::Enabled>,
+        dma_tx: &dma::Handle<TxStream::Instance, state::Enabled>,
+        rx: RxStream,
+        tx: TxStream,
+    ) -> Transfer<Word, I, P, B, RxStream, TxStream, dma::Ready, Role>
     where
-        Rx<I>: dma::Target,
-        Tx<I>: dma::Target,
+        Rx<I>: dma::Target<RxStream>,
+        Tx<I>: dma::Target<TxStream>,
+        RxStream: dma::Stream,
+        TxStream: dma::Stream,
         B: DerefMut + 'static,
         B::Target: AsMutSlice<Element = Word>,
     {
@@ -170,13 +416,112 @@ where
             _state: dma::Ready,
         }
     }
+
+    /// Start an SPI transfer that is driven by the SPI interrupt
+    ///
+    /// This is an alternative to [`Spi::transfer_all`] for when no DMA streams
+    /// are available. Sends the data in `buffer` and writes the received data
+    /// into buffer right after. The TXE, RXNE and error interrupts are enabled
+    /// for the duration of the transfer, and
+    /// [`InterruptTransfer::on_interrupt`] needs to be called from the
+    /// interrupt handler of the SPI peripheral to move the data.
+    ///
+    /// The RX FIFO threshold is configured so RXNE is raised for every
+    /// received frame. Each interrupt fills the TX FIFO as far as possible,
+    /// without sending more frames ahead than the RX FIFO can hold.
+    ///
+    /// Unlike DMA transfers, these transfers don't send or check a hardware
+    /// CRC.
+    pub fn transfer_interrupt<B>(self, buffer: Pin<B>) -> InterruptTransfer<Word, I, P, B, Role>
+    where
+        B: DerefMut,
+        B::Target: AsMutSlice<Element = Word> + Unpin,
+    {
+        let transfer = InterruptTransfer {
+            buffer,
+            target: self,
+            sent: 0,
+            received: 0,
+            error: None,
+        };
+
+        if !transfer.buffer.as_slice().is_empty() {
+            transfer.target.spi.set_interrupts(true, true, true);
+        }
+
+        transfer
+    }
+}
+
+impl<I, SCK, MISO, MOSI, NSS, Word> Spi<I, (SCK, MISO, MOSI, NSS), Enabled<Word>>
+where
+    I: Instance,
+    SCK: Sck<I>,
+    MISO: Miso<I>,
+    MOSI: Mosi<I>,
+    NSS: Nss<I>,
+    Word: SupportedWord,
+{
+    /// Asserts the NSS pin
+    ///
+    /// The peripheral drives NSS low while it is enabled, which it is after
+    /// [`Spi::enable`].
+    pub fn select(&mut self) {
+        self.spi.enable();
+    }
+
+    /// Deasserts the NSS pin, once the last frame has been sent
+    pub fn deselect(&mut self) {
+        self.spi.disable();
+    }
+
+    /// Enables or disables NSS pulses between frames
+    ///
+    /// With pulses enabled, NSS is deasserted for one clock period between
+    /// consecutive frames, even during continuous transfers. This only works
+    /// with [`Phase::CaptureOnFirstTransition`].
+    pub fn set_nss_pulse(&mut self, enabled: bool) {
+        self.spi.set_nss_pulse(enabled);
+    }
 }
 
-impl<I, P, Word> FullDuplex<Word> for Spi<I, P, Enabled<Word>>
+impl<I, P, Word, Role> Spi<I, P, Enabled<Word, Role>>
 where
     I: Instance,
     P: Pins<I>,
-    Word: SupportedWordSize,
+    Word: SupportedWord,
+{
+    /// Enables or disables hardware CRC calculation
+    ///
+    /// With DMA, the CRC is sent after the data automatically, and checked
+    /// by [`Transfer::wait`]. The DMA buffer must not include the CRC.
+    pub fn set_crc(&mut self, crc: Option<Crc>) {
+        self.spi.set_crc(crc);
+    }
+
+    /// Sends the CRC after the current frame
+    ///
+    /// Call this right after writing the last data word using
+    /// [`FullDuplex::send`], when not using DMA.
+    pub fn send_crc(&mut self) {
+        self.spi.send_crc();
+    }
+
+    /// Waits for the CRC to be received and checks it
+    ///
+    /// The received CRC is discarded and the CRC calculation is reset for the
+    /// next transfer. Returns [`Error::Crc`] if the CRC doesn't match. Does
+    /// nothing if CRC calculation is disabled.
+    pub fn check_crc(&mut self) -> Result<(), Error> {
+        self.spi.check_crc()
+    }
+}
+
+impl<I, P, Word, Role> FullDuplex<Word> for Spi<I, P, Enabled<Word, Role>>
+where
+    I: Instance,
+    P: Pins<I>,
+    Word: SupportedWord,
 {
     type Error = Error;
 
@@ -189,27 +534,27 @@ where
     }
 }
 
-impl<I, P, Word> transfer::Default<Word> for Spi<I, P, Enabled<Word>>
+impl<I, P, Word, Role> transfer::Default<Word> for Spi<I, P, Enabled<Word, Role>>
 where
     I: Instance,
     P: Pins<I>,
-    Word: SupportedWordSize,
+    Word: SupportedWord,
 {
 }
 
-impl<I, P, Word> write::Default<Word> for Spi<I, P, Enabled<Word>>
+impl<I, P, Word, Role> write::Default<Word> for Spi<I, P, Enabled<Word, Role>>
 where
     I: Instance,
     P: Pins<I>,
-    Word: SupportedWordSize,
+    Word: SupportedWord,
 {
 }
 
-impl<I, P, Word> write_iter::Default<Word> for Spi<I, P, Enabled<Word>>
+impl<I, P, Word, Role> write_iter::Default<Word> for Spi<I, P, Enabled<Word, Role>>
 where
     I: Instance,
     P: Pins<I>,
-    Word: SupportedWordSize,
+    Word: SupportedWord,
 {
 }
 
@@ -229,20 +574,79 @@ where
 /// Users of this crate should not implement this trait.
 pub trait Instance {
     fn enable_clock(&self, rcc: &mut Rcc);
-    fn configure<Word>(&self, br: u8, cpol: bool, cpha: bool)
+    fn clock(clocks: &Clocks) -> Hertz;
+    fn configure<Word>(&self, settings: Settings)
     where
         Word: SupportedWordSize;
     fn read<Word>(&self) -> nb::Result<Word, Error>
     where
-        Word: SupportedWordSize;
+        Word: dma::SupportedWordSize;
     fn send<Word>(&self, word: Word) -> nb::Result<(), Error>
     where
-        Word: SupportedWordSize;
+        Word: dma::SupportedWordSize;
     fn dr_address(&self) -> u32;
+    fn flush(&self);
+    fn enable(&self);
+    fn disable(&self);
+    fn set_nss_pulse(&self, enabled: bool);
+    fn set_clock(&self, br: u8, cpol: bool, cpha: bool);
+    fn set_crc(&self, crc: Option<Crc>);
+    fn set_output(&self, output: bool);
+    fn set_interrupts(&self, txe: bool, rxne: bool, err: bool);
+    fn receive<Word>(&self, buffer: &mut [Word]) -> Result<(), Error>
+    where
+        Word: dma::SupportedWordSize;
+    fn send_crc(&self);
+    fn check_crc(&self) -> Result<(), Error>;
+}
+
+/// Configuration of the SPI peripheral, as applied by [`Instance::configure`]
+#[derive(Clone, Copy)]
+pub struct Settings {
+    /// Baud rate divider, as written to the BR field
+    pub br: u8,
+    pub cpol: bool,
+    pub cpha: bool,
+    /// Slave mode with hardware NSS input, instead of master mode
+    pub slave: bool,
+    /// Drive the NSS pin in master mode
+    pub nss_output: bool,
+    /// TI frame format, instead of Motorola
+    pub ti: bool,
+    pub line_mode: LineMode,
+}
+
+impl Settings {
+    /// Full-duplex master settings with the given baud rate divider and mode
+    fn new(br: u8, mode: Mode) -> Self {
+        Self {
+            br,
+            cpol: mode.polarity == Polarity::IdleHigh,
+            cpha: mode.phase == Phase::CaptureOnSecondTransition,
+            slave: false,
+            nss_output: false,
+            ti: false,
+            line_mode: LineMode::FullDuplex,
+        }
+    }
+}
+
+/// Use of the data lines, see [`Settings`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineMode {
+    /// MOSI and MISO are used at the same time
+    FullDuplex,
+    /// A single bidirectional data line, MOSI in master mode
+    Bidirectional,
+    /// MISO only, with the clock running while the peripheral is enabled
+    ReceiveOnly,
 }
 
 /// Implemented for all tuples that contain a full set of valid SPI pins
-pub trait Pins<I> {}
+pub trait Pins<I> {
+    /// Whether the set includes an NSS pin
+    const NSS: bool = false;
+}
 
 impl<I, SCK, MISO, MOSI> Pins<I> for (SCK, MISO, MOSI)
 where
@@ -252,6 +656,16 @@ where
 {
 }
 
+impl<I, SCK, MISO, MOSI, NSS> Pins<I> for (SCK, MISO, MOSI, NSS)
+where
+    SCK: Sck<I>,
+    MISO: Miso<I>,
+    MOSI: Mosi<I>,
+    NSS: Nss<I>,
+{
+    const NSS: bool = true;
+}
+
 /// Implemented for all pins that can function as the SCK pin
 ///
 /// Users of this crate should not implement this trait.
@@ -267,15 +681,21 @@ pub trait Miso<I> {}
 /// Users of this crate should not implement this trait.
 pub trait Mosi<I> {}
 
+/// Implemented for all pins that can function as the NSS pin
+///
+/// Users of this crate should not implement this trait.
+pub trait Nss<I> {}
+
 macro_rules! impl_instance {
     (
         $(
             $name:ty {
-                regs: ($bus:ident, $reset:ident, $enable:ident),
+                regs: ($bus:ident, $rstr:ident, $reset:ident, $enable:ident, $pclk:ident),
                 pins: {
                     SCK: [$($sck:ty,)*],
                     MISO: [$($miso:ty,)*],
                     MOSI: [$($mosi:ty,)*],
+                    NSS: [$($nss:ty,)*],
                 }
             }
         )*
@@ -287,6 +707,10 @@ macro_rules! impl_instance {
                     rcc.$bus.enr().modify(|_, w| w.$enable().enabled());
                 }
 
+                fn clock(clocks: &Clocks) -> Hertz {
+                    clocks.$pclk()
+                }
+
                 // I don't like putting this much code into the macro, but I
                 // have to: There are two different SPI variants in the PAC, and
                 // while I haven't found any actual differences between them,
@@ -297,7 +721,7 @@ macro_rules! impl_instance {
                 // Maybe this is a problem in the SVD file that can be fixed
                 // there.
 
-                fn configure<Word>(&self, br: u8, cpol: bool, cpha: bool)
+                fn configure<Word>(&self, settings: Settings)
                     where Word: SupportedWordSize
                 {
                     self.cr2.write(|w| {
@@ -317,11 +741,11 @@ macro_rules! impl_instance {
                             // Disable error interrupt
                             .errie().masked()
                             // Frame format
-                            .frf().motorola()
+                            .frf().bit(settings.ti)
                             // NSS pulse management
                             .nssp().no_pulse()
                             // SS output
-                            .ssoe().disabled()
+                            .ssoe().bit(settings.nss_output)
                             // Enable DMA support
                             .txdmaen().enabled()
                             .rxdmaen().enabled()
@@ -329,27 +753,36 @@ macro_rules! impl_instance {
 
                     self.cr1.write(|w|
                         w
-                            // Use two lines for MISO/MOSI
-                            .bidimode().unidirectional()
+                            // Use one bidirectional or two unidirectional
+                            // lines for MISO/MOSI
+                            .bidimode()
+                                .bit(settings.line_mode == LineMode::Bidirectional)
+                            // Start out receiving in bidirectional mode
+                            .bidioe().output_disabled()
                             // Disable hardware CRC calculation
                             .crcen().disabled()
-                            // Enable full-duplex mode
-                            .rxonly().full_duplex()
-                            // Manage slave select pin manually
-                            .ssm().enabled()
+                            // Select full-duplex or receive-only mode
+                            .rxonly()
+                                .bit(settings.line_mode == LineMode::ReceiveOnly)
+                            // Manage slave select pin manually in master
+                            // mode, unless it's driven by the hardware. Use
+                            // the NSS input in slave mode.
+                            .ssm().bit(!settings.slave && !settings.nss_output)
                             .ssi().set_bit()
                             // Transmit most significant bit first
                             .lsbfirst().msbfirst()
                             // Set baud rate value
-                            .br().bits(br)
-                            // Select master mode
-                            .mstr().master()
+                            .br().bits(settings.br)
+                            // Select master or slave mode
+                            .mstr().bit(!settings.slave)
                             // Select clock polarity
-                            .cpol().bit(cpol)
+                            .cpol().bit(settings.cpol)
                             // Select clock phase
-                            .cpha().bit(cpha)
-                            // Enable SPI
-                            .spe().enabled()
+                            .cpha().bit(settings.cpha)
+                            // Enable SPI. In the other modes, the clock
+                            // starts running right away, so leave it
+                            // disabled until data is read.
+                            .spe().bit(settings.line_mode == LineMode::FullDuplex)
                     );
                 }
 
@@ -431,6 +864,178 @@ macro_rules! impl_instance {
                 fn dr_address(&self) -> u32 {
                     &self.dr as *const _ as _
                 }
+
+                fn flush(&self) {
+                    let cr1 = self.cr1.read().bits();
+                    let cr2 = self.cr2.read().bits();
+                    let crcpr = self.crcpr.read().bits();
+
+                    // The TX FIFO can't be flushed other than by resetting the
+                    // peripheral.
+                    //
+                    // NOTE(unsafe) The reset register is only written to
+                    // during initialization otherwise.
+                    let rcc = unsafe { &*pac::RCC::ptr() };
+                    rcc.$rstr.modify(|_, w| w.$reset().set_bit());
+                    rcc.$rstr.modify(|_, w| w.$reset().clear_bit());
+
+                    // Restore the configuration, but leave the peripheral
+                    // disabled.
+                    //
+                    // This is safe, as we're writing back values that were
+                    // read from the same registers. CRCPR has to be restored
+                    // before CRCEN is set again in CR1.
+                    self.crcpr.write(|w| unsafe { w.bits(crcpr) });
+                    self.cr2.write(|w| unsafe { w.bits(cr2) });
+                    self.cr1.write(|w| unsafe { w.bits(cr1) }.spe().disabled());
+                }
+
+                fn enable(&self) {
+                    self.cr1.modify(|_, w| w.spe().enabled());
+                }
+
+                fn disable(&self) {
+                    // Wait until the last frame has been sent. See the
+                    // procedure for disabling the SPI in the reference
+                    // manual.
+                    while !self.sr.read().ftlvl().is_empty() {}
+                    while self.sr.read().bsy().is_busy() {}
+
+                    self.cr1.modify(|_, w| w.spe().disabled());
+                }
+
+                fn set_nss_pulse(&self, enabled: bool) {
+                    let spe = self.cr1.read().spe().is_enabled();
+
+                    self.disable();
+                    self.cr2.modify(|_, w| w.nssp().bit(enabled));
+                    if spe {
+                        self.enable();
+                    }
+                }
+
+                fn set_clock(&self, br: u8, cpol: bool, cpha: bool) {
+                    self.disable();
+                    self.cr1.modify(|_, w| {
+                        w
+                            .br().bits(br)
+                            .cpol().bit(cpol)
+                            .cpha().bit(cpha)
+                    });
+                    self.enable();
+                }
+
+                fn set_crc(&self, crc: Option<Crc>) {
+                    let spe = self.cr1.read().spe().is_enabled();
+
+                    self.disable();
+                    // Writing CRCEN also resets the CRC calculation
+                    self.cr1.modify(|_, w| w.crcen().disabled());
+                    if let Some(crc) = crc {
+                        self.crcpr.write(|w| w.crcpoly().bits(crc.polynomial));
+                        self.cr1.modify(|_, w| {
+                            w
+                                .crcl().bit(crc.length == CrcLength::Bits16)
+                                .crcen().enabled()
+                        });
+                    }
+                    if spe {
+                        self.enable();
+                    }
+                }
+
+                fn set_output(&self, output: bool) {
+                    self.cr1.modify(|_, w| w.bidioe().bit(output));
+                }
+
+                fn set_interrupts(&self, txe: bool, rxne: bool, err: bool) {
+                    self.cr2.modify(|_, w| {
+                        w
+                            .txeie().bit(txe)
+                            .rxneie().bit(rxne)
+                            .errie().bit(err)
+                    });
+                }
+
+                fn receive<Word>(&self, buffer: &mut [Word]) -> Result<(), Error>
+                    where Word: dma::SupportedWordSize
+                {
+                    if buffer.is_empty() {
+                        return Ok(());
+                    }
+
+                    // The clock runs as long as the peripheral is enabled. See
+                    // the procedure for disabling the SPI in receive-only mode
+                    // in the reference manual.
+                    self.enable();
+
+                    let len = buffer.len();
+                    for (i, word) in buffer.iter_mut().enumerate() {
+                        if i + 1 == len {
+                            // The last frame is ongoing, stop after it
+                            self.cr1.modify(|_, w| w.spe().disabled());
+                        }
+
+                        match nb::block!(self.read()) {
+                            Ok(value) => *word = value,
+                            Err(err) => {
+                                self.cr1.modify(|_, w| w.spe().disabled());
+                                return Err(err);
+                            }
+                        }
+                    }
+
+                    // Discard any frames received after the last one
+                    while self.sr.read().bsy().is_busy() {}
+                    while !self.sr.read().frlvl().is_empty() {
+                        // This is safe, as `&self.dr` is a memory-mapped
+                        // register.
+                        let _: Word = unsafe {
+                            ptr::read_volatile(&self.dr as *const _ as *const _)
+                        };
+                    }
+
+                    Ok(())
+                }
+
+                fn send_crc(&self) {
+                    self.cr1.modify(|_, w| w.crcnext().set_bit());
+                }
+
+                fn check_crc(&self) -> Result<(), Error> {
+                    let cr1 = self.cr1.read();
+                    if cr1.crcen().is_disabled() {
+                        return Ok(());
+                    }
+
+                    // Wait for the CRC to be received. It's always written to
+                    // the RX FIFO, even when using DMA, so discard it.
+                    while !self.sr.read().ftlvl().is_empty() {}
+                    while self.sr.read().bsy().is_busy() {}
+                    while !self.sr.read().frlvl().is_empty() {
+                        // This is safe, as `&self.dr` is a memory-mapped
+                        // register.
+                        let _: u8 = unsafe {
+                            ptr::read_volatile(&self.dr as *const _ as *const _)
+                        };
+                    }
+
+                    let result = if self.sr.read().crcerr().is_no_match() {
+                        // The flag is cleared by writing 0
+                        self.sr.modify(|_, w| w.crcerr().clear_bit());
+                        Err(Error::Crc)
+                    } else {
+                        Ok(())
+                    };
+
+                    // Reset the CRC calculation for the next transfer
+                    self.cr1.modify(|_, w| w.spe().disabled());
+                    self.cr1.modify(|_, w| w.crcen().disabled());
+                    self.cr1.modify(|_, w| w.crcen().enabled());
+                    self.cr1.modify(|_, w| w.spe().enabled());
+
+                    result
+                }
             }
 
             $(
@@ -444,13 +1049,17 @@ macro_rules! impl_instance {
             $(
                 impl Mosi<$name> for $mosi {}
             )*
+
+            $(
+                impl Nss<$name> for $nss {}
+            )*
         )*
     }
 }
 
 impl_instance!(
     pac::SPI1 {
-        regs: (apb2, spi1rst, spi1en),
+        regs: (apb2, apb2rstr, spi1rst, spi1en, pclk2),
         pins: {
             SCK: [
                 gpio::gpioa::PA5<Alternate<AF5>>,
@@ -467,10 +1076,15 @@ impl_instance!(
                 gpio::gpiob::PB5<Alternate<AF5>>,
                 gpio::gpiod::PD7<Alternate<AF5>>,
             ],
+            NSS: [
+                gpio::gpioa::PA4<Alternate<AF5>>,
+                gpio::gpioa::PA15<Alternate<AF5>>,
+                gpio::gpiog::PG10<Alternate<AF5>>,
+            ],
         }
     }
     pac::SPI2 {
-        regs: (apb1, spi2rst, spi2en),
+        regs: (apb1, apb1rstr, spi2rst, spi2en, pclk1),
         pins: {
             SCK: [
                 gpio::gpioa::PA9<Alternate<AF5>>,
@@ -490,10 +1104,15 @@ impl_instance!(
                 gpio::gpioc::PC3<Alternate<AF5>>,
                 gpio::gpioi::PI3<Alternate<AF5>>,
             ],
+            NSS: [
+                gpio::gpiob::PB9<Alternate<AF5>>,
+                gpio::gpiob::PB12<Alternate<AF5>>,
+                gpio::gpioi::PI0<Alternate<AF5>>,
+            ],
         }
     }
     pac::SPI3 {
-        regs: (apb1, spi3rst, spi3en),
+        regs: (apb1, apb1rstr, spi3rst, spi3en, pclk1),
         pins: {
             SCK: [
                 gpio::gpiob::PB3<Alternate<AF6>>,
@@ -509,10 +1128,14 @@ impl_instance!(
                 gpio::gpioc::PC12<Alternate<AF6>>,
                 gpio::gpiod::PD6<Alternate<AF5>>,
             ],
+            NSS: [
+                gpio::gpioa::PA4<Alternate<AF6>>,
+                gpio::gpioa::PA15<Alternate<AF6>>,
+            ],
         }
     }
     pac::SPI4 {
-        regs: (apb2, spi4rst, spi4en),
+        regs: (apb2, apb2rstr, spi4rst, spi4en, pclk2),
         pins: {
             SCK: [
                 gpio::gpioe::PE2<Alternate<AF5>>,
@@ -526,10 +1149,14 @@ impl_instance!(
                 gpio::gpioe::PE6<Alternate<AF5>>,
                 gpio::gpioe::PE14<Alternate<AF5>>,
             ],
+            NSS: [
+                gpio::gpioe::PE4<Alternate<AF5>>,
+                gpio::gpioe::PE11<Alternate<AF5>>,
+            ],
         }
     }
     pac::SPI5 {
-        regs: (apb2, spi5rst, spi5en),
+        regs: (apb2, apb2rstr, spi5rst, spi5en, pclk2),
         pins: {
             SCK: [
                 gpio::gpiof::PF7<Alternate<AF5>>,
@@ -543,6 +1170,10 @@ impl_instance!(
                 gpio::gpiof::PF9<Alternate<AF5>>,
                 gpio::gpiof::PF11<Alternate<AF5>>,
             ],
+            NSS: [
+                gpio::gpiof::PF6<Alternate<AF5>>,
+                gpio::gpioh::PH5<Alternate<AF5>>,
+            ],
         }
     }
 );
@@ -560,7 +1191,7 @@ impl_instance!(
 ))]
 impl_instance!(
     pac::SPI6 {
-        regs: (apb2, spi6rst, spi6en),
+        regs: (apb2, apb2rstr, spi6rst, spi6en, pclk2),
         pins: {
             SCK: [
                 gpio::gpiog::PG13<Alternate<AF5>>,
@@ -571,6 +1202,9 @@ impl_instance!(
             MOSI: [
                 gpio::gpiog::PG14<Alternate<AF5>>,
             ],
+            NSS: [
+                gpio::gpiog::PG8<Alternate<AF5>>,
+            ],
         }
     }
 );
@@ -587,38 +1221,75 @@ impl<I> Miso<I> for NoMiso {}
 pub struct NoMosi;
 impl<I> Mosi<I> for NoMosi {}
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub enum Error {
     FrameFormat,
     Overrun,
     ModeFault,
+    /// The received CRC didn't match the calculated one
+    Crc,
+    /// A DMA transfer failed
+    Dma(dma::Error),
+}
+
+/// Hardware CRC configuration
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Crc {
+    /// The CRC polynomial, without the most significant bit, e.g. `0x1021`
+    /// for CRC-16-CCITT
+    pub polynomial: u16,
+    pub length: CrcLength,
+}
+
+/// Length of the CRC
+///
+/// The CRC length can only be chosen for 8-bit and 16-bit frames. For other
+/// frame sizes, it is the same as the frame size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CrcLength {
+    Bits8,
+    Bits16,
 }
 
 /// RX token used for DMA transfers
-pub struct Rx<I>(PhantomData<I>);
+pub struct Rx<I>(pub(crate) PhantomData<I>);
 
 /// TX token used for DMA transfers
-pub struct Tx<I>(PhantomData<I>);
+pub struct Tx<I>(pub(crate) PhantomData<I>);
 
 /// A DMA transfer of the SPI peripheral
 ///
 /// Since DMA can send and receive at the same time, using two DMA transfers and
 /// two DMA streams, we need this type to represent this operation and wrap the
 /// underlying [`dma::Transfer`] instances.
-pub struct Transfer<Word: SupportedWordSize, I, P, Buffer, Rx: dma::Target, Tx: dma::Target, State>
+pub struct Transfer<
+    Word: SupportedWord,
+    I,
+    P,
+    Buffer,
+    RxStream: dma::Stream,
+    TxStream: dma::Stream,
+    State,
+    Role = Master,
+> where
+    Rx<I>: dma::Target<RxStream>,
+    Tx<I>: dma::Target<TxStream>,
 {
     buffer: Pin<Buffer>,
-    target: Spi<I, P, Enabled<Word>>,
-    rx: dma::Transfer<Rx, dma::PtrBuffer<Word>, State>,
-    tx: dma::Transfer<Tx, dma::PtrBuffer<Word>, State>,
+    target: Spi<I, P, Enabled<Word, Role>>,
+    rx: dma::Transfer<Rx<I>, RxStream, dma::PtrBuffer<Word>, State>,
+    tx: dma::Transfer<Tx<I>, TxStream, dma::PtrBuffer<Word>, State>,
     _state: State,
 }
 
-impl<Word, I, P, Buffer, Rx, Tx> Transfer<Word, I, P, Buffer, Rx, Tx, dma::Ready>
+impl<Word, I, P, Buffer, RxStream, TxStream, Role>
+    Transfer<Word, I, P, Buffer, RxStream, TxStream, dma::Ready, Role>
 where
-    Rx: dma::Target,
-    Tx: dma::Target,
-    Word: SupportedWordSize,
+    Rx<I>: dma::Target<RxStream>,
+    Tx<I>: dma::Target<TxStream>,
+    RxStream: dma::Stream,
+    TxStream: dma::Stream,
+    Word: SupportedWord,
 {
     /// Enables the given interrupts for this DMA transfer
     ///
@@ -627,8 +1298,8 @@ where
     /// DMA streams.
     pub fn enable_interrupts(
         &mut self,
-        rx_handle: &dma::Handle<Rx::Instance, state::Enabled>,
-        tx_handle: &dma::Handle<Tx::Instance, state::Enabled>,
+        rx_handle: &dma::Handle<RxStream::Instance, state::Enabled>,
+        tx_handle: &dma::Handle<TxStream::Instance, state::Enabled>,
         interrupts: dma::Interrupts,
     ) {
         self.rx.enable_interrupts(rx_handle, interrupts);
@@ -641,9 +1312,9 @@ where
     /// its type state set to indicate the transfer has been started.
     pub fn start(
         self,
-        rx_handle: &dma::Handle<Rx::Instance, state::Enabled>,
-        tx_handle: &dma::Handle<Tx::Instance, state::Enabled>,
-    ) -> Transfer<Word, I, P, Buffer, Rx, Tx, dma::Started> {
+        rx_handle: &dma::Handle<RxStream::Instance, state::Enabled>,
+        tx_handle: &dma::Handle<TxStream::Instance, state::Enabled>,
+    ) -> Transfer<Word, I, P, Buffer, RxStream, TxStream, dma::Started, Role> {
         Transfer {
             buffer: self.buffer,
             target: self.target,
@@ -654,17 +1325,21 @@ where
     }
 }
 
-impl<Word, I, P, Buffer, Rx, Tx> Transfer<Word, I, P, Buffer, Rx, Tx, dma::Started>
+impl<Word, I, P, Buffer, RxStream, TxStream, Role>
+    Transfer<Word, I, P, Buffer, RxStream, TxStream, dma::Started, Role>
 where
-    Rx: dma::Target,
-    Tx: dma::Target,
-    Word: SupportedWordSize,
+    I: Instance,
+    Rx<I>: dma::Target<RxStream>,
+    Tx<I>: dma::Target<TxStream>,
+    RxStream: dma::Stream,
+    TxStream: dma::Stream,
+    Word: SupportedWord,
 {
     /// Checks whether the transfer is still ongoing
     pub fn is_active(
         &self,
-        rx_handle: &dma::Handle<Rx::Instance, state::Enabled>,
-        tx_handle: &dma::Handle<Tx::Instance, state::Enabled>,
+        rx_handle: &dma::Handle<RxStream::Instance, state::Enabled>,
+        tx_handle: &dma::Handle<TxStream::Instance, state::Enabled>,
     ) -> bool {
         self.rx.is_active(rx_handle) || self.tx.is_active(tx_handle)
     }
@@ -679,14 +1354,14 @@ where
     /// data buffer, the DMA stream, and the peripheral. Those have been moved
     /// into the `Transfer` instance to prevent concurrent access to them. This
     /// method returns those resources, so they can be used again.
+    ///
+    /// If hardware CRC calculation is enabled, the received CRC is checked
+    /// once the transfer has ended.
     pub fn wait(
         self,
-        rx_handle: &dma::Handle<Rx::Instance, state::Enabled>,
-        tx_handle: &dma::Handle<Tx::Instance, state::Enabled>,
-    ) -> Result<
-        TransferResources<Word, I, P, Rx, Tx, Buffer>,
-        (TransferResources<Word, I, P, Rx, Tx, Buffer>, dma::Error),
-    > {
+        rx_handle: &dma::Handle<RxStream::Instance, state::Enabled>,
+        tx_handle: &dma::Handle<TxStream::Instance, state::Enabled>,
+    ) -> TransferResult<Word, I, P, RxStream, TxStream, Buffer, Role> {
         let (rx_res, rx_err) = match self.rx.wait(rx_handle) {
             Ok(res) => (res, None),
             Err((res, err)) => (res, Some(err)),
@@ -704,9 +1379,12 @@ where
         };
 
         if let Some(err) = rx_err {
-            return Err((res, err));
+            return Err((res, Error::Dma(err)));
         }
         if let Some(err) = tx_err {
+            return Err((res, Error::Dma(err)));
+        }
+        if let Err(err) = res.target.spi.check_crc() {
             return Err((res, err));
         }
 
@@ -714,40 +1392,283 @@ where
     }
 }
 
+impl<Word, I, P, Buffer, RxStream, TxStream>
+    Transfer<Word, I, P, Buffer, RxStream, TxStream, dma::Started, Slave>
+where
+    I: Instance,
+    Rx<I>: dma::Target<RxStream>,
+    Tx<I>: dma::Target<TxStream>,
+    RxStream: dma::Stream,
+    TxStream: dma::Stream,
+    Word: SupportedWord,
+{
+    /// Re-arms the transfer for the next chip select assertion
+    ///
+    /// Call this after the master has deasserted NSS, e.g. from an EXTI
+    /// interrupt on the rising edge of the NSS pin. The transfer restarts at
+    /// the beginning of the buffer, with the first words pre-loaded into the
+    /// TX FIFO. Returns the number of words that were received since the
+    /// transfer was started or last re-armed.
+    pub fn rearm(
+        &mut self,
+        rx_handle: &dma::Handle<RxStream::Instance, state::Enabled>,
+        tx_handle: &dma::Handle<TxStream::Instance, state::Enabled>,
+    ) -> usize {
+        let received = self.rx.rewind(rx_handle);
+        self.tx.rewind(tx_handle);
+
+        // Discard the words left over in the FIFOs
+        self.target.spi.flush();
+
+        self.rx.resume(rx_handle);
+        self.tx.resume(tx_handle);
+        self.target.spi.enable();
+
+        received
+    }
+}
+
+/// The result of [`Transfer::wait`]
+///
+/// On error, the resources are returned along with the error.
+pub type TransferResult<Word, I, P, RxStream, TxStream, Buffer, Role> = Result<
+    TransferResources<Word, I, P, RxStream, TxStream, Buffer, Role>,
+    (
+        TransferResources<Word, I, P, RxStream, TxStream, Buffer, Role>,
+        Error,
+    ),
+>;
+
 /// The resources that an ongoing transfer needs exclusive access to
-pub struct TransferResources<Word, I, P, Rx: dma::Target, Tx: dma::Target, Buffer> {
-    pub rx_stream: Rx::Stream,
-    pub tx_stream: Tx::Stream,
-    pub target: Spi<I, P, Enabled<Word>>,
+pub struct TransferResources<Word, I, P, RxStream, TxStream, Buffer, Role = Master> {
+    pub rx_stream: RxStream,
+    pub tx_stream: TxStream,
+    pub target: Spi<I, P, Enabled<Word, Role>>,
     pub buffer: Pin<Buffer>,
 }
 
 // As `TransferResources` is used in the error variant of `Result`, it needs a
 // `Debug` implementation to enable stuff like `unwrap` and `expect`. This can't
 // be derived without putting requirements on the type arguments.
-impl<Word, I, P, Rx, Tx, Buffer> fmt::Debug for TransferResources<Word, I, P, Rx, Tx, Buffer>
-where
-    Rx: dma::Target,
-    Tx: dma::Target,
+impl<Word, I, P, RxStream, TxStream, Buffer, Role> fmt::Debug
+    for TransferResources<Word, I, P, RxStream, TxStream, Buffer, Role>
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "TransferResources {{ .. }}")
     }
 }
 
+/// An SPI transfer that is driven by the SPI interrupt
+///
+/// Created by [`Spi::transfer_interrupt`].
+///
+/// Dropping an ongoing transfer disables the SPI interrupts again.
+pub struct InterruptTransfer<Word, I, P, Buffer, Role = Master>
+where
+    I: Instance,
+{
+    buffer: Pin<Buffer>,
+    target: Spi<I, P, Enabled<Word, Role>>,
+    sent: usize,
+    received: usize,
+    error: Option<Error>,
+}
+
+impl<Word, I, P, Buffer, Role> InterruptTransfer<Word, I, P, Buffer, Role>
+where
+    I: Instance,
+    Word: SupportedWord,
+    Buffer: DerefMut,
+    Buffer::Target: AsMutSlice<Element = Word> + Unpin,
+{
+    /// Moves data between the buffer and the SPI FIFOs
+    ///
+    /// Call this from the interrupt handler of the SPI peripheral. Returns
+    /// `WouldBlock` while the transfer is still ongoing, and `Ok` once all
+    /// words have been sent and received. If the peripheral reports an error,
+    /// the transfer is aborted and the error is returned.
+    ///
+    /// The interrupts are disabled once the transfer has ended, so this
+    /// shouldn't get called again after that. If it is, it returns the same
+    /// result again.
+    pub fn on_interrupt(&mut self) -> nb::Result<(), Error> {
+        if let Some(err) = self.error {
+            return Err(nb::Error::Other(err));
+        }
+
+        let spi = &self.target.spi;
+        let buffer = self.buffer.as_mut_slice();
+        let len = buffer.len();
+
+        // The FIFOs are 32 bits deep. Never send more frames ahead than the
+        // RX FIFO can hold, or received frames would get lost.
+        let depth = 4 / core::mem::size_of::<Word>();
+
+        let result = 'transfer: loop {
+            // Words are received in the order they were sent, so we only ever
+            // overwrite words in the buffer that have already been sent.
+            while self.received < self.sent {
+                match spi.read() {
+                    Ok(word) => {
+                        buffer[self.received] = word;
+                        self.received += 1;
+                    }
+                    Err(nb::Error::WouldBlock) => break,
+                    Err(nb::Error::Other(err)) => break 'transfer Err(err),
+                }
+            }
+
+            if self.sent == len {
+                break Ok(());
+            }
+            if self.sent - self.received >= depth {
+                break Ok(());
+            }
+
+            match spi.send(buffer[self.sent]) {
+                Ok(()) => self.sent += 1,
+                Err(nb::Error::WouldBlock) => break Ok(()),
+                Err(nb::Error::Other(err)) => break Err(err),
+            }
+        };
+
+        if let Err(err) = result {
+            spi.set_interrupts(false, false, false);
+            self.error = Some(err);
+            return Err(nb::Error::Other(err));
+        }
+
+        if self.received == len {
+            spi.set_interrupts(false, false, false);
+            return Ok(());
+        }
+        if self.sent == len {
+            // Nothing left to send, so the TXE interrupt would only keep
+            // firing.
+            spi.set_interrupts(false, true, true);
+        }
+
+        Err(nb::Error::WouldBlock)
+    }
+
+    /// Checks whether the transfer is still ongoing
+    pub fn is_active(&self) -> bool {
+        self.error.is_none() && self.received < self.buffer.as_slice().len()
+    }
+
+    /// Waits for the transfer to end
+    ///
+    /// This method will block if the transfer is still ongoing, moving the
+    /// data itself instead of waiting for the interrupt. If you want this
+    /// method to return immediately, first check whether the transfer is still
+    /// ongoing by calling `is_active`.
+    ///
+    /// Returns the SPI peripheral and the buffer, so they can be used again.
+    pub fn wait(mut self) -> InterruptTransferResult<Word, I, P, Buffer, Role> {
+        let result = nb::block!(self.on_interrupt());
+
+        // The interrupts are disabled by now, so skip `drop`
+        let transfer = ManuallyDrop::new(self);
+
+        // This is safe, as `transfer` is neither used nor dropped afterwards,
+        // so the fields are only moved out once.
+        let res = unsafe {
+            InterruptTransferResources {
+                target: ptr::read(&transfer.target),
+                buffer: ptr::read(&transfer.buffer),
+            }
+        };
+
+        match result {
+            Ok(()) => Ok(res),
+            Err(err) => Err((res, err)),
+        }
+    }
+}
+
+impl<Word, I, P, Buffer, Role> Drop for InterruptTransfer<Word, I, P, Buffer, Role>
+where
+    I: Instance,
+{
+    fn drop(&mut self) {
+        // Otherwise the interrupts would keep firing, without anyone to
+        // handle them
+        self.target.spi.set_interrupts(false, false, false);
+    }
+}
+
+/// The result of [`InterruptTransfer::wait`]
+///
+/// On error, the resources are returned along with the error.
+pub type InterruptTransferResult<Word, I, P, Buffer, Role> = Result<
+    InterruptTransferResources<Word, I, P, Buffer, Role>,
+    (InterruptTransferResources<Word, I, P, Buffer, Role>, Error),
+>;
+
+/// The resources that an ongoing interrupt-driven transfer needs exclusive
+/// access to
+pub struct InterruptTransferResources<Word, I, P, Buffer, Role = Master> {
+    pub target: Spi<I, P, Enabled<Word, Role>>,
+    pub buffer: Pin<Buffer>,
+}
+
+// As `InterruptTransferResources` is used in the error variant of `Result`, it
+// needs a `Debug` implementation to enable stuff like `unwrap` and `expect`.
+impl<Word, I, P, Buffer, Role> fmt::Debug for InterruptTransferResources<Word, I, P, Buffer, Role> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "InterruptTransferResources {{ .. }}")
+    }
+}
+
 /// Indicates that the SPI peripheral is enabled
 ///
-/// The `Word` type parameter indicates which word size the peripheral is
-/// configured for.
-pub struct Enabled<Word>(PhantomData<Word>);
+/// The `Word` type parameter indicates the type that frames are exchanged
+/// as, `u8` or `u16`, depending on the frame size the peripheral is
+/// configured for. `Role` indicates whether it is the [`Master`] or a
+/// [`Slave`].
+pub struct Enabled<Word, Role = Master>(PhantomData<(Word, Role)>);
+
+/// The [`Enabled`] state for the data frame size `Size`
+pub type EnabledWordSize<Size, Role = Master> = Enabled<<Size as SupportedWordSize>::Word, Role>;
+
+/// Indicates that the SPI peripheral is enabled, using a single
+/// bidirectional data line
+pub struct Bidirectional<Word>(PhantomData<Word>);
+
+/// Indicates that the SPI peripheral is enabled in receive-only mode
+pub struct ReceiveOnly<Word>(PhantomData<Word>);
+
+/// Indicates that the SPI peripheral is the bus master, generating the clock
+pub struct Master;
+
+/// Indicates that the SPI peripheral is a slave, selected through NSS
+pub struct Slave;
+
+/// Implemented for all supported data frame sizes
+///
+/// `u8` and `u16` select 8-bit and 16-bit frames. The other frame sizes are
+/// selected by markers like [`Bits12`]. Frames of up to 8 bits are exchanged
+/// as `u8`, longer ones as `u16`, right-aligned.
+pub trait SupportedWordSize: private::Sealed {
+    /// The type that a frame is stored in
+    type Word: SupportedWord;
 
-pub trait SupportedWordSize: dma::SupportedWordSize + private::Sealed {
     fn frxth() -> cr2::FRXTH_A;
     fn ds() -> cr2::DS_A;
 }
 
+/// Implemented for the types that frames are exchanged as
+///
+/// Used as a frame size, `u8` and `u16` select 8-bit and 16-bit frames.
+pub trait SupportedWord: SupportedWordSize<Word = Self> + dma::SupportedWordSize + Copy {}
+
+impl SupportedWord for u8 {}
+impl SupportedWord for u16 {}
+
 impl private::Sealed for u8 {}
 impl SupportedWordSize for u8 {
+    type Word = u8;
+
     fn frxth() -> cr2::FRXTH_A {
         cr2::FRXTH_A::QUARTER
     }
@@ -759,6 +1680,8 @@ impl SupportedWordSize for u8 {
 
 impl private::Sealed for u16 {}
 impl SupportedWordSize for u16 {
+    type Word = u16;
+
     fn frxth() -> cr2::FRXTH_A {
         cr2::FRXTH_A::HALF
     }
@@ -768,6 +1691,44 @@ impl SupportedWordSize for u16 {
     }
 }
 
+macro_rules! word_sizes {
+    ($($size:ident: ($word:ty, $ds:ident, $frxth:ident),)+) => {
+        $(
+            /// Data frame size marker
+            pub struct $size;
+
+            impl private::Sealed for $size {}
+            impl SupportedWordSize for $size {
+                type Word = $word;
+
+                fn frxth() -> cr2::FRXTH_A {
+                    cr2::FRXTH_A::$frxth
+                }
+
+                fn ds() -> cr2::DS_A {
+                    cr2::DS_A::$ds
+                }
+            }
+        )+
+    }
+}
+
+word_sizes! {
+    Bits4: (u8, FOURBIT, QUARTER),
+    Bits5: (u8, FIVEBIT, QUARTER),
+    Bits6: (u8, SIXBIT, QUARTER),
+    Bits7: (u8, SEVENBIT, QUARTER),
+    Bits8: (u8, EIGHTBIT, QUARTER),
+    Bits9: (u16, NINEBIT, HALF),
+    Bits10: (u16, TENBIT, HALF),
+    Bits11: (u16, ELEVENBIT, HALF),
+    Bits12: (u16, TWELVEBIT, HALF),
+    Bits13: (u16, THIRTEENBIT, HALF),
+    Bits14: (u16, FOURTEENBIT, HALF),
+    Bits15: (u16, FIFTEENBIT, HALF),
+    Bits16: (u16, SIXTEENBIT, HALF),
+}
+
 mod private {
     /// Prevents code outside of the parent module from implementing traits
     ///
@@ -776,3 +1737,26 @@ mod private {
     /// be implemented only in the parent module.
     pub trait Sealed {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::calculate_br;
+
+    #[test]
+    fn br_exact() {
+        assert_eq!(calculate_br(108_000_000, 54_000_000), (0, 54_000_000));
+        assert_eq!(calculate_br(54_000_000, 1_687_500), (4, 1_687_500));
+    }
+
+    #[test]
+    fn br_rounds_down() {
+        assert_eq!(calculate_br(108_000_000, 10_000_000), (3, 6_750_000));
+        assert_eq!(calculate_br(108_000_000, 200_000_000), (0, 54_000_000));
+    }
+
+    #[test]
+    fn br_too_low() {
+        assert_eq!(calculate_br(108_000_000, 400_000), (7, 421_875));
+        assert_eq!(calculate_br(108_000_000, 0), (7, 421_875));
+    }
+}
@@ -0,0 +1,218 @@
+//! Sharing one SPI bus between multiple devices
+//!
+//! [`SharedBus`] wraps an enabled SPI peripheral and hands out [`SpiDevice`]
+//! proxies. Each device has its own chip select pin, mode and frequency. The
+//! peripheral is reconfigured whenever a transaction is made by a different
+//! device than the previous one.
+//!
+//! Transactions run inside a critical section, so devices can be used from
+//! different interrupt handlers or RTIC tasks. Keep them short, as interrupts
+//! are disabled for their whole duration.
+
+use core::{cell::RefCell, convert::Infallible};
+
+use cortex_m::interrupt::{self, Mutex};
+use embedded_hal::{
+    blocking::spi::{Transfer, Write, WriteIter},
+    digital::v2::OutputPin,
+};
+
+use super::{
+    calculate_br, Enabled, Error, Instance, Mode, Phase, Pins, Polarity, Spi, SupportedWord,
+};
+use crate::{rcc::Clocks, time::Hertz};
+
+/// An SPI bus that is shared between multiple devices
+///
+/// Created by [`Spi::into_shared`].
+pub struct SharedBus<I, P, Word> {
+    /// Only `None` while the bus is being released
+    bus: Mutex<RefCell<Option<Bus<I, P, Word>>>>,
+    clock: Hertz,
+}
+
+struct Bus<I, P, Word> {
+    spi: Spi<I, P, Enabled<Word>>,
+    /// The configuration of the last transaction, if known
+    config: Option<DeviceConfig>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct DeviceConfig {
+    br: u8,
+    cpol: bool,
+    cpha: bool,
+}
+
+impl<I, P, Word> Spi<I, P, Enabled<Word>>
+where
+    I: Instance,
+    P: Pins<I>,
+    Word: SupportedWord,
+{
+    /// Turns the SPI peripheral into a bus that can be shared between
+    /// multiple devices
+    pub fn into_shared(self, clocks: &Clocks) -> SharedBus<I, P, Word> {
+        SharedBus {
+            bus: Mutex::new(RefCell::new(Some(Bus {
+                spi: self,
+                config: None,
+            }))),
+            clock: I::clock(clocks),
+        }
+    }
+}
+
+impl<I, P, Word> SharedBus<I, P, Word>
+where
+    I: Instance,
+    P: Pins<I>,
+    Word: SupportedWord,
+{
+    /// Creates a device on this bus
+    ///
+    /// The chip select pin is driven high right away, and low for the
+    /// duration of every transaction. Picks the fastest clock divider that
+    /// doesn't exceed `frequency`, and returns the device together with the
+    /// actual SCK frequency.
+    ///
+    /// If `frequency` is lower than the bus clock divided by 256, the slowest
+    /// clock divider is used, and the returned frequency is higher than
+    /// requested.
+    pub fn device<CS>(
+        &self,
+        mut cs: CS,
+        mode: Mode,
+        frequency: Hertz,
+    ) -> (SpiDevice<'_, I, P, Word, CS>, Hertz)
+    where
+        CS: OutputPin<Error = Infallible>,
+    {
+        let (br, actual) = calculate_br(self.clock.0, frequency.0);
+
+        // The error type is `Infallible`
+        let _ = cs.set_high();
+
+        let device = SpiDevice {
+            bus: self,
+            cs,
+            config: DeviceConfig {
+                br,
+                cpol: mode.polarity == Polarity::IdleHigh,
+                cpha: mode.phase == Phase::CaptureOnSecondTransition,
+            },
+        };
+        (device, Hertz(actual))
+    }
+
+    /// Returns the SPI peripheral
+    ///
+    /// This is only possible once all devices have been dropped.
+    pub fn release(self) -> Spi<I, P, Enabled<Word>> {
+        // There are no devices left that could access the bus
+        interrupt::free(|cs| self.bus.borrow(cs).replace(None))
+            .unwrap()
+            .spi
+    }
+
+    /// Runs a transaction for the device with the given configuration
+    fn transaction<CS, R>(
+        &self,
+        config: DeviceConfig,
+        cs: &mut CS,
+        f: impl FnOnce(&mut Spi<I, P, Enabled<Word>>) -> R,
+    ) -> R
+    where
+        CS: OutputPin<Error = Infallible>,
+    {
+        interrupt::free(|cs_token| {
+            let mut bus = self.bus.borrow(cs_token).borrow_mut();
+            let bus = bus.as_mut().unwrap();
+
+            if bus.config != Some(config) {
+                bus.spi.spi.set_clock(config.br, config.cpol, config.cpha);
+                bus.config = Some(config);
+            }
+
+            // The error type is `Infallible`
+            let _ = cs.set_low();
+            let result = f(&mut bus.spi);
+            let _ = cs.set_high();
+
+            result
+        })
+    }
+}
+
+/// A device on a shared SPI bus
+///
+/// Created by [`SharedBus::device`]. Implements the blocking SPI traits, with
+/// every call being one transaction during which the chip select pin is low.
+pub struct SpiDevice<'a, I, P, Word, CS> {
+    bus: &'a SharedBus<I, P, Word>,
+    cs: CS,
+    config: DeviceConfig,
+}
+
+impl<'a, I, P, Word, CS> SpiDevice<'a, I, P, Word, CS> {
+    /// Destroys the device and returns its chip select pin
+    pub fn free(self) -> CS {
+        self.cs
+    }
+}
+
+macro_rules! impl_blocking {
+    ($($word:ty,)*) => {
+        $(
+            impl<'a, I, P, CS> Transfer<$word> for SpiDevice<'a, I, P, $word, CS>
+            where
+                I: Instance,
+                P: Pins<I>,
+                CS: OutputPin<Error = Infallible>,
+            {
+                type Error = Error;
+
+                fn transfer<'w>(&mut self, words: &'w mut [$word]) -> Result<&'w [$word], Error> {
+                    self.bus.transaction(self.config, &mut self.cs, move |spi| {
+                        Transfer::transfer(spi, words)
+                    })
+                }
+            }
+
+            impl<'a, I, P, CS> Write<$word> for SpiDevice<'a, I, P, $word, CS>
+            where
+                I: Instance,
+                P: Pins<I>,
+                CS: OutputPin<Error = Infallible>,
+            {
+                type Error = Error;
+
+                fn write(&mut self, words: &[$word]) -> Result<(), Error> {
+                    self.bus.transaction(self.config, &mut self.cs, move |spi| {
+                        Write::write(spi, words)
+                    })
+                }
+            }
+
+            impl<'a, I, P, CS> WriteIter<$word> for SpiDevice<'a, I, P, $word, CS>
+            where
+                I: Instance,
+                P: Pins<I>,
+                CS: OutputPin<Error = Infallible>,
+            {
+                type Error = Error;
+
+                fn write_iter<WI>(&mut self, words: WI) -> Result<(), Error>
+                where
+                    WI: IntoIterator<Item = $word>,
+                {
+                    self.bus.transaction(self.config, &mut self.cs, move |spi| {
+                        WriteIter::write_iter(spi, words)
+                    })
+                }
+            }
+        )*
+    }
+}
+
+impl_blocking!(u8, u16,);